@@ -0,0 +1,37 @@
+use std::{env, fmt::Write, fs, path::Path};
+
+/// With the `embed` feature, list the inputs and examples to compile into the
+/// binary, taking the encrypted copy of the files that only exist encrypted.
+fn main() {
+    println!("cargo:rerun-if-changed=input");
+    if env::var_os("CARGO_FEATURE_EMBED").is_none() {
+        return;
+    }
+
+    let root = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("input");
+    let mut inputs = String::from("[\n");
+    for day in 1..=12 {
+        let name = format!("day{:02}.txt", day);
+        let input = embed(&root.join(&name));
+        let example = embed(&root.join("examples").join(&name));
+        writeln!(inputs, "    ({}, {}),", input, example).unwrap();
+    }
+    inputs.push(']');
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("inputs.rs");
+    fs::write(out, inputs).unwrap();
+}
+
+fn embed(path: &Path) -> String {
+    let encrypted = path.with_extension("txt.enc");
+    if path.exists() {
+        format!("Embedded::Plain(include_str!({:?}))", path)
+    } else if encrypted.exists() {
+        format!("Embedded::Encrypted(include_bytes!({:?}))", encrypted)
+    } else {
+        panic!(
+            "cannot embed {}: neither it nor its encrypted copy exists",
+            path.display()
+        );
+    }
+}
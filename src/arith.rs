@@ -0,0 +1,175 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use clap::ValueEnum;
+use num::{
+    traits::{CheckedAdd, CheckedMul, CheckedSub},
+    BigInt,
+};
+
+/// Integer width used to accumulate answers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Precision {
+    #[default]
+    I64,
+    I128,
+    Big,
+}
+
+static PRECISION: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_precision(precision: Precision) {
+    PRECISION.store(precision as u8, Ordering::Relaxed);
+}
+
+pub fn precision() -> Precision {
+    match PRECISION.load(Ordering::Relaxed) {
+        1 => Precision::I128,
+        2 => Precision::Big,
+        _ => Precision::I64,
+    }
+}
+
+/// Arithmetic operation whose result does not fit in its integer type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub op: char,
+    pub lhs: String,
+    pub rhs: String,
+}
+
+impl Overflow {
+    fn new(op: char, lhs: &impl fmt::Display, rhs: &impl fmt::Display) -> Self {
+        Overflow {
+            op,
+            lhs: lhs.to_string(),
+            rhs: rhs.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "arithmetic overflow computing {} {} {}",
+            self.lhs, self.op, self.rhs
+        )
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+pub fn add<T: CheckedAdd + fmt::Display>(a: T, b: T) -> Result<T, Overflow> {
+    a.checked_add(&b).ok_or_else(|| Overflow::new('+', &a, &b))
+}
+
+pub fn sub<T: CheckedSub + fmt::Display>(a: T, b: T) -> Result<T, Overflow> {
+    a.checked_sub(&b).ok_or_else(|| Overflow::new('-', &a, &b))
+}
+
+pub fn mul<T: CheckedMul + fmt::Display>(a: T, b: T) -> Result<T, Overflow> {
+    a.checked_mul(&b).ok_or_else(|| Overflow::new('*', &a, &b))
+}
+
+/// Puzzle answer, accumulated with the configured precision.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    I64(i64),
+    I128(i128),
+    Big(BigInt),
+}
+
+impl Answer {
+    /// Answer holding `x`, failing when it does not fit in the configured precision.
+    pub fn new(x: impl Into<i128>) -> Result<Self, Overflow> {
+        let x = x.into();
+        Ok(match precision() {
+            Precision::I64 => {
+                Answer::I64(i64::try_from(x).map_err(|_| Overflow::new('+', &0, &x))?)
+            }
+            Precision::I128 => Answer::I128(x),
+            Precision::Big => Answer::Big(BigInt::from(x)),
+        })
+    }
+
+    pub fn zero() -> Self {
+        Answer::new(0).unwrap()
+    }
+
+    pub fn one() -> Self {
+        Answer::new(1).unwrap()
+    }
+
+    pub fn add(&mut self, x: impl Into<i128>) -> Result<(), Overflow> {
+        let x = x.into();
+        match self {
+            Answer::I64(v) => {
+                *v = add(*v, i64::try_from(x).map_err(|_| Overflow::new('+', v, &x))?)?
+            }
+            Answer::I128(v) => *v = add(*v, x)?,
+            Answer::Big(v) => *v += x,
+        }
+        Ok(())
+    }
+
+    pub fn mul(&mut self, x: impl Into<i128>) -> Result<(), Overflow> {
+        let x = x.into();
+        match self {
+            Answer::I64(v) => {
+                *v = mul(*v, i64::try_from(x).map_err(|_| Overflow::new('*', v, &x))?)?
+            }
+            Answer::I128(v) => *v = mul(*v, x)?,
+            Answer::Big(v) => *v *= x,
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::I64(v) => write!(f, "{}", v),
+            Answer::I128(v) => write!(f, "{}", v),
+            Answer::Big(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl PartialEq<i64> for Answer {
+    fn eq(&self, other: &i64) -> bool {
+        match self {
+            Answer::I64(v) => v == other,
+            Answer::I128(v) => *v == *other as i128,
+            Answer::Big(v) => *v == BigInt::from(*other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arith::{add, mul, Answer};
+
+    #[test]
+    fn test1() {
+        assert!(add(i32::MAX - 1, 1) == Ok(i32::MAX));
+        let err = mul(i32::MAX, 2).unwrap_err();
+        assert!(err.to_string() == "arithmetic overflow computing 2147483647 * 2");
+    }
+
+    #[test]
+    fn test2() {
+        let mut answer = Answer::I64(i64::MAX);
+        assert!(answer.add(1).is_err());
+
+        let mut answer = Answer::I128(i64::MAX as i128);
+        answer.add(1).unwrap();
+        assert!(answer.to_string() == "9223372036854775808");
+
+        let mut answer = Answer::Big(i128::MAX.into());
+        answer.mul(4).unwrap();
+        assert!(answer.to_string() == "680564733841876926926749214863536422908");
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    env, fs,
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{self, Key};
+
+pub const CONFIG_FILE: &str = "aoc.toml";
+
+/// How answers are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+/// When to color the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    /// Color when printing to a terminal and NO_COLOR is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Settings read from `aoc.toml`, every one of them being optional.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Directory holding the `dayNN.txt` inputs
+    pub input_dir: PathBuf,
+    pub year: u32,
+    /// File holding the session cookie, used when AOC_SESSION is not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_file: Option<PathBuf>,
+    /// File holding the passphrase of encrypted inputs, used when AOC_INPUT_KEY is not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<PathBuf>,
+    /// URL of the private leaderboard JSON export
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leaderboard: Option<String>,
+    pub format: Format,
+    pub color: Color,
+    /// Timeout of network requests, in seconds
+    pub timeout: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            input_dir: PathBuf::from("./input"),
+            year: 2023,
+            session_file: None,
+            key_file: None,
+            leaderboard: None,
+            format: Format::default(),
+            color: Color::default(),
+            timeout: 30,
+        }
+    }
+}
+
+/// Locations searched for a configuration file, in order: the project root, then
+/// the user configuration directory.
+pub fn candidates() -> Vec<PathBuf> {
+    let mut candidates = vec![PathBuf::from(CONFIG_FILE)];
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+    if let Some(config_home) = config_home {
+        candidates.push(config_home.join("aoc").join(CONFIG_FILE));
+    }
+    candidates
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        Config::parse(&text).with_context(|| format!("invalid configuration in {}", path.display()))
+    }
+
+    /// Load the given file, or the first existing candidate, falling back to the
+    /// defaults. Also returns the file the settings come from.
+    pub fn find(path: Option<&Path>) -> Result<(Self, Option<PathBuf>)> {
+        if let Some(path) = path {
+            return Ok((Config::load(path)?, Some(path.to_path_buf())));
+        }
+        match candidates().into_iter().find(|path| path.is_file()) {
+            Some(path) => Ok((Config::load(&path)?, Some(path))),
+            None => Ok((Config::default(), None)),
+        }
+    }
+
+    pub fn input(&self, day: u32) -> PathBuf {
+        self.input_dir.join(format!("day{:02}.txt", day))
+    }
+
+    /// Example of the puzzle statement, kept in the `examples` subdirectory.
+    pub fn example(&self, day: u32) -> PathBuf {
+        self.input_dir
+            .join("examples")
+            .join(format!("day{:02}.txt", day))
+    }
+
+    pub fn input_path(&self, day: u32, example: bool) -> PathBuf {
+        match example {
+            true => self.example(day),
+            false => self.input(day),
+        }
+    }
+
+    /// Session cookie, taken from AOC_SESSION or from the session file.
+    pub fn session(&self) -> Result<String> {
+        if let Ok(session) = env::var("AOC_SESSION") {
+            return Ok(session);
+        }
+        let Some(path) = &self.session_file else {
+            bail!("AOC_SESSION is not set and no session file is configured");
+        };
+        let session =
+            fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        Ok(session.trim().to_string())
+    }
+
+    /// Key of the encrypted inputs, derived from AOC_INPUT_KEY or from the key file.
+    pub fn key(&self) -> Result<Key> {
+        if let Ok(passphrase) = env::var(crypto::KEY_VAR) {
+            return Ok(Key::new(&passphrase));
+        }
+        let Some(path) = &self.key_file else {
+            bail!(
+                "{} is not set and no key file is configured",
+                crypto::KEY_VAR
+            );
+        };
+        let passphrase =
+            fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        Ok(Key::new(&passphrase))
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
+
+    pub fn use_color(&self) -> bool {
+        match self.color {
+            Color::Always => true,
+            Color::Never => false,
+            Color::Auto => env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal(),
+        }
+    }
+
+    pub fn show(&self) -> String {
+        toml::to_string(self).expect("configuration is always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::config::{Color, Config, Format};
+
+    #[test]
+    fn test1() {
+        let config =
+            Config::parse("input_dir = \"/data/aoc\"\nformat = \"json\"\ntimeout = 5\n").unwrap();
+        assert!(config.input(3) == Path::new("/data/aoc/day03.txt"));
+        assert!(config.input_path(3, true) == Path::new("/data/aoc/examples/day03.txt"));
+        assert!(config.format == Format::Json);
+        assert!(config.color == Color::Auto && config.year == 2023);
+        assert!(Config::parse(&config.show()).unwrap() == config);
+    }
+
+    #[test]
+    fn test2() {
+        let err = Config::parse("colour = \"never\"").unwrap_err();
+        assert!(err.to_string().contains("unknown field `colour`"));
+        assert!(Config::parse("timeout = \"5s\"").is_err());
+    }
+}
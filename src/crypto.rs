@@ -0,0 +1,133 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use argon2::Argon2;
+use chacha20poly1305::{aead::Aead, ChaCha20Poly1305, KeyInit, Nonce};
+
+/// Environment variable holding the passphrase of the encrypted inputs.
+pub const KEY_VAR: &str = "AOC_INPUT_KEY";
+
+/// Header of encrypted files, followed by the salt, the nonce and the ciphertext.
+const MAGIC: &[u8] = b"AOCENC2\n";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Passphrase of the inputs, surrounding whitespace ignored.
+pub struct Key(String);
+
+impl Key {
+    pub fn new(passphrase: &str) -> Self {
+        Key(passphrase.trim().to_string())
+    }
+
+    /// Derive the cipher of a file from the passphrase and the salt stored in its
+    /// header, with Argon2 so that guessing the passphrase is slow.
+    fn cipher(&self, salt: &[u8]) -> ChaCha20Poly1305 {
+        let mut key = [0; 32];
+        Argon2::default()
+            .hash_password_into(self.0.as_bytes(), salt, &mut key)
+            .expect("the salt and key lengths are valid");
+        ChaCha20Poly1305::new(&key.into())
+    }
+}
+
+/// Where the encrypted copy of an input is stored: `dayNN.txt.enc`.
+pub fn encrypted_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".enc");
+    PathBuf::from(path)
+}
+
+pub fn encrypt(key: &Key, text: &str) -> Vec<u8> {
+    let salt = rand::random::<[u8; SALT_LEN]>();
+    let nonce = rand::random::<[u8; NONCE_LEN]>();
+    let ciphertext = key
+        .cipher(&salt)
+        .encrypt(Nonce::from_slice(&nonce), text.as_bytes())
+        .expect("encryption never fails on in-memory buffers");
+    [MAGIC, &salt, &nonce, &ciphertext].concat()
+}
+
+pub fn decrypt(key: &Key, data: &[u8]) -> Result<String> {
+    let Some(data) = data
+        .strip_prefix(MAGIC)
+        .filter(|data| data.len() >= SALT_LEN + NONCE_LEN)
+    else {
+        bail!("not an encrypted input");
+    };
+    let (salt, data) = data.split_at(SALT_LEN);
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let Ok(text) = key
+        .cipher(salt)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+    else {
+        bail!("wrong key or corrupted file");
+    };
+    Ok(String::from_utf8(text)?)
+}
+
+/// Decrypt the file at `path`.
+pub fn read(key: &Key, path: &Path) -> Result<String> {
+    let data = fs::read(path).with_context(|| format!("cannot read {}", path.display()))?;
+    decrypt(key, &data).with_context(|| format!("cannot decrypt {}", path.display()))
+}
+
+/// Replace the plain text input at `path` by its encrypted copy, returning the
+/// path of the copy.
+pub fn encrypt_file(key: &Key, path: &Path, keep: bool) -> Result<PathBuf> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    let encrypted = encrypted_path(path);
+    fs::write(&encrypted, encrypt(key, &text))
+        .with_context(|| format!("cannot write {}", encrypted.display()))?;
+    if !keep {
+        fs::remove_file(path).with_context(|| format!("cannot remove {}", path.display()))?;
+    }
+    Ok(encrypted)
+}
+
+/// Restore the plain text input at `path` from its encrypted copy.
+pub fn decrypt_file(key: &Key, path: &Path, keep: bool) -> Result<PathBuf> {
+    let encrypted = encrypted_path(path);
+    let text = read(key, &encrypted)?;
+    fs::write(path, text).with_context(|| format!("cannot write {}", path.display()))?;
+    if !keep {
+        fs::remove_file(&encrypted)
+            .with_context(|| format!("cannot remove {}", encrypted.display()))?;
+    }
+    Ok(encrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::crypto::{decrypt, encrypt, encrypted_path, Key};
+
+    #[test]
+    fn test1() {
+        let text = "1abc2\npqr3stu8vwx\n";
+        let data = encrypt(&Key::new("hunter2\n"), text);
+        assert!(!data.windows(5).any(|w| w == b"1abc2"));
+        assert!(decrypt(&Key::new("hunter2"), &data).unwrap() == text);
+        assert!(encrypt(&Key::new("hunter2"), text) != data);
+        assert!(data.starts_with(b"AOCENC2\n"));
+    }
+
+    #[test]
+    fn test2() {
+        let data = encrypt(&Key::new("hunter2"), "Time: 7 15 30");
+        assert!(decrypt(&Key::new("hunter3"), &data).is_err());
+        // flip a bit of the salt, then of the ciphertext
+        for i in [8, data.len() - 1] {
+            let mut tampered = data.clone();
+            tampered[i] ^= 1;
+            assert!(decrypt(&Key::new("hunter2"), &tampered).is_err());
+        }
+        assert!(decrypt(&Key::new("hunter2"), b"Time: 7 15 30").is_err());
+        assert!(encrypted_path(Path::new("input/day06.txt")) == Path::new("input/day06.txt.enc"));
+    }
+}
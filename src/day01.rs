@@ -1,89 +1,305 @@
-use anyhow::{bail, Result};
-use std::collections::HashMap;
-
-use crate::lines_from_file;
-
-fn check_for_digit(substr: String) -> Option<char> {
-    let digits_map = HashMap::from([
-        ("zero", '0'),
-        ("one", '1'),
-        ("two", '2'),
-        ("three", '3'),
-        ("four", '4'),
-        ("five", '5'),
-        ("six", '6'),
-        ("seven", '7'),
-        ("eight", '8'),
-        ("nine", '9'),
-    ]);
-
-    for (k, v) in digits_map.iter() {
-        if substr.starts_with(k) {
-            return Some(*v);
-        }
-    }
+use std::{cmp::Reverse, fs, io::BufRead, ops::Range, path::Path};
+
+use aho_corasick::AhoCorasick;
+use anyhow::{bail, Context, Result};
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    arith::Answer,
+    error::ParseError,
+    registry::{Implementation, Inspector, Solution},
+    setting::Setting,
+};
+
+const DAY: u32 = 1;
 
-    None
+const WORDS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// Tokens spelling the digits, English unless set from the command line.
+pub static VOCABULARY: Setting<Vocabulary> = Setting::new("vocabulary", Vocabulary::english);
+
+/// Token matched in a line.
+#[derive(Debug, PartialEq, Eq)]
+struct Token {
+    /// Byte span in the line
+    span: Range<usize>,
+    value: u64,
+    /// Whether the token is an ASCII digit rather than a word
+    digit: bool,
 }
 
-fn parse_line(str: String) -> Result<i32> {
-    let mut first: Option<char> = None;
-    let mut last: Option<char> = None;
+/// Tokens standing for digits in calibration lines, along with their value.
+/// The ASCII digits are always part of it.
+pub struct Vocabulary {
+    automaton: AhoCorasick,
+    values: Vec<u64>,
+}
 
-    for i in 0..str.len() {
-        if first.is_none() {
-            let c = str.chars().nth(i).unwrap();
-            if c.is_ascii_digit() {
-                first = Some(c);
-            } else {
-                first = check_for_digit(str[i..].to_string());
+impl Vocabulary {
+    pub fn new<'a>(words: impl IntoIterator<Item = (&'a str, u64)>) -> Result<Self> {
+        let mut tokens = (0..10)
+            .map(|d| (d.to_string(), d))
+            .collect::<Vec<(String, u64)>>();
+        for (word, value) in words {
+            if word.is_empty() {
+                bail!("empty token");
             }
+            if tokens.iter().any(|(token, _)| token == word) {
+                bail!("token `{}` defined twice", word);
+            }
+            tokens.push((word.to_string(), value));
         }
+        let automaton = AhoCorasick::new(tokens.iter().map(|(token, _)| token))?;
+        let values = tokens.into_iter().map(|(_, value)| value).collect();
+        Ok(Vocabulary { automaton, values })
+    }
 
-        if last.is_none() {
-            let c = str.chars().nth(str.len() - i - 1).unwrap();
-            if c.is_ascii_digit() {
-                last = Some(c);
-            } else {
-                last = check_for_digit(str[(str.len() - i - 1)..].to_string());
+    /// Digits and their English names.
+    pub fn english() -> Self {
+        Vocabulary::new(WORDS.into_iter().zip(0..)).expect("English words are distinct")
+    }
+
+    /// Read `token = value` lines, ignoring blank lines and `#` comments.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut words = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
             }
+            let Some((word, value)) = line.split_once('=') else {
+                bail!("line {}: expected `<token> = <value>`", i + 1);
+            };
+            let value = value
+                .trim()
+                .parse::<u64>()
+                .with_context(|| format!("line {}: invalid value", i + 1))?;
+            words.push((word.trim(), value));
         }
+        Vocabulary::new(words)
+    }
 
-        if first.is_some() && last.is_some() {
-            break;
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        Vocabulary::parse(&text)
+            .with_context(|| format!("invalid vocabulary in {}", path.display()))
+    }
+
+    /// Values of the first and last tokens of `line`, found in a single pass.
+    /// Overlapping tokens all count, so `eightwo` ends with `two`, and the
+    /// longest token wins among those starting at the same position.
+    ///
+    /// The automaton runs over bytes, but as tokens are valid UTF-8 their
+    /// matches always start and end on character boundaries.
+    fn first_last(&self, line: &str) -> Option<(Token, Token)> {
+        let mut matches = self.automaton.find_overlapping_iter(line);
+        let m = matches.next()?;
+        let (first, last) = matches.fold((m, m), |(first, last), m| {
+            let first = match (m.start(), Reverse(m.len())) < (first.start(), Reverse(first.len()))
+            {
+                true => m,
+                false => first,
+            };
+            let last = match (m.start(), m.len()) > (last.start(), last.len()) {
+                true => m,
+                false => last,
+            };
+            (first, last)
+        });
+        Some((self.token(first), self.token(last)))
+    }
+
+    fn token(&self, m: aho_corasick::Match) -> Token {
+        Token {
+            span: m.range(),
+            value: self.values[m.pattern()],
+            digit: m.pattern().as_usize() < 10,
         }
     }
+}
 
-    let catstr = match (first, last) {
-        (Some(f), Some(l)) => format!("{}{}", f, l),
-        _ => String::from(""),
+/// Calibration value of a line: the values of its first and last tokens written
+/// one after the other.
+fn parse_line(line: &str, vocabulary: &Vocabulary) -> Result<u64> {
+    let Some((first, last)) = vocabulary.first_last(line) else {
+        bail!("no digit found in line");
     };
+    value(&first, &last)
+}
+
+fn value(first: &Token, last: &Token) -> Result<u64> {
+    let (first, last) = (first.value, last.value);
+    format!("{}{}", first, last)
+        .parse::<u64>()
+        .with_context(|| format!("calibration value {}{} is too large", first, last))
+}
 
-    match catstr.parse::<i32>() {
-        Ok(res) => Ok(res),
-        Err(e) => bail!("Failed to parse string: {}", e),
+fn process(input: &[String], vocabulary: &Vocabulary) -> Result<Answer> {
+    let mut add = Answer::zero();
+    for (i, line) in input.iter().enumerate() {
+        match parse_line(line, vocabulary) {
+            Ok(res) => add.add(res)?,
+            Err(e) => return Err(ParseError::line(DAY, i, line, e.to_string()).into()),
+        }
     }
+    Ok(add)
 }
 
-fn process(input: Vec<String>) -> Result<i32> {
-    let mut add = 0;
-    for str in input {
-        match parse_line(str) {
-            Ok(res) => add += res,
-            Err(e) => bail!("Failed to parse line: {}", e),
+/// Where a streamed computation stands.
+pub struct Progress<'a> {
+    pub lines: u64,
+    pub bytes: u64,
+    pub sum: &'a Answer,
+}
+
+/// Sum the calibration values read from `reader` one line at a time, in constant
+/// memory, calling `progress` every `every` lines and at the end.
+pub fn process_stream(
+    mut reader: impl BufRead,
+    every: u64,
+    mut progress: impl FnMut(&Progress),
+) -> Result<Answer> {
+    let vocabulary = VOCABULARY.get();
+    let mut add = Answer::zero();
+    let (mut lines, mut bytes) = (0, 0);
+    let mut buf = String::new();
+    loop {
+        buf.clear();
+        let len = reader
+            .read_line(&mut buf)
+            .with_context(|| format!("cannot read line {}", lines + 1))?;
+        if len == 0 {
+            break;
+        }
+        let line = buf.strip_suffix('\n').unwrap_or(&buf);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match parse_line(line, vocabulary) {
+            Ok(res) => add.add(res)?,
+            Err(e) => return Err(ParseError::line(DAY, lines as usize, line, e.to_string()).into()),
+        }
+        lines += 1;
+        bytes += len as u64;
+        if every > 0 && lines % every == 0 {
+            progress(&Progress {
+                lines,
+                bytes,
+                sum: &add,
+            });
         }
     }
+    if every == 0 || lines % every != 0 {
+        progress(&Progress {
+            lines,
+            bytes,
+            sum: &add,
+        });
+    }
     Ok(add)
 }
 
-pub fn run() {
-    let res = process(lines_from_file("./input/day01.txt"));
-    println!("{:?}", res.unwrap());
+fn describe(line: &str, token: &Token) -> String {
+    let kind = if token.digit { "digit" } else { "word" };
+    format!(
+        "`{}` {}..{} {}",
+        &line[token.span.clone()],
+        token.span.start,
+        token.span.end,
+        kind
+    )
+}
+
+/// Tokens picked on each line and the resulting calibration value.
+fn explain(input: &[String], vocabulary: &Vocabulary) -> Vec<String> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let Some((first, last)) = vocabulary.first_last(line) else {
+                return format!("{:>4}: NO DIGIT in {:?}", i + 1, line);
+            };
+            let value = match value(&first, &last) {
+                Ok(value) => value.to_string(),
+                Err(e) => e.to_string(),
+            };
+            format!(
+                "{:>4}: {} (first {}, last {})",
+                i + 1,
+                value,
+                describe(line, &first),
+                describe(line, &last)
+            )
+        })
+        .collect()
+}
+
+fn show_explain(input: &[String]) -> Result<String> {
+    Ok(explain(input, VOCABULARY.get()).join("\n"))
+}
+
+/// Tokens of each line, as matched by the vocabulary.
+fn parse(input: &[String]) -> Result<()> {
+    let vocabulary = VOCABULARY.get();
+    for (i, line) in input.iter().enumerate() {
+        if vocabulary.first_last(line).is_none() {
+            return Err(ParseError::line(DAY, i, line, "no digit found in line").into());
+        }
+    }
+    Ok(())
+}
+
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| match parse_line(line, VOCABULARY.get()) {
+            Ok(_) => None,
+            Err(e) => Some(ParseError::line(DAY, i, line, e.to_string())),
+        })
+        .collect()
+}
+
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            let mut line = String::new();
+            for _ in 0..rng.gen_range(1..5) {
+                for _ in 0..rng.gen_range(0..4) {
+                    line.push(rng.gen_range('a'..='z'));
+                }
+                match rng.gen_bool(0.5) {
+                    true => line.push(rng.gen_range('0'..='9')),
+                    false => line.push_str(WORDS[rng.gen_range(0..WORDS.len())]),
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse,
+        generate,
+        parts: vec![vec![Implementation::new("default", |input| {
+            process(input, VOCABULARY.get())
+        })]],
+        inspectors: vec![Inspector {
+            name: "explain",
+            show: show_explain,
+        }],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day01::process;
+    use crate::day01::{explain, process, process_stream, Vocabulary};
 
     #[test]
     fn test1() {
@@ -93,7 +309,7 @@ mod tests {
             "a1b2c3d4e5f".into(),
             "treb7uchet".into(),
         ];
-        assert!(process(input).unwrap() == 142);
+        assert!(process(&input, &Vocabulary::english()).unwrap() == 142);
     }
 
     #[test]
@@ -107,6 +323,100 @@ mod tests {
             "zoneight234".into(),
             "7pqrstsixteen".into(),
         ];
-        assert!(process(input).unwrap() == 281);
+        assert!(process(&input, &Vocabulary::english()).unwrap() == 281);
+    }
+
+    #[test]
+    fn test3() {
+        let vocabulary = Vocabulary::english();
+        let input = vec![
+            "eightwo".to_string(),
+            "oneight".into(),
+            "twone1".into(),
+            "sevenine".into(),
+        ];
+        assert!(process(&input, &vocabulary).unwrap() == 82 + 18 + 21 + 79);
+        assert!(process(&["abc".to_string()], &vocabulary).is_err());
+    }
+
+    #[test]
+    fn test4() {
+        let vocabulary =
+            Vocabulary::parse("# French\nun = 1\ndeux=2\n\nonze = 11\ndix = 10").unwrap();
+        let input = vec![
+            "undeux".to_string(),
+            "onzeXdix".into(),
+            "trois3dix".into(),
+            "one".into(),
+        ];
+        assert!(process(&input[..3], &vocabulary).unwrap() == 12 + 1110 + 310);
+        assert!(process(&input, &vocabulary).is_err());
+
+        let vocabulary = Vocabulary::parse("seven = 7\nseventeen = 17\nteen = 0").unwrap();
+        assert!(process(&["seventeen".to_string()], &vocabulary).unwrap() == 170);
+
+        assert!(Vocabulary::parse("un = 1\nun = 2").is_err());
+        assert!(Vocabulary::parse("7 = 8").is_err());
+        assert!(Vocabulary::parse("un: 1").is_err());
+        assert!(Vocabulary::parse(" = 1").is_err());
+    }
+
+    #[test]
+    fn test5() {
+        let vocabulary = Vocabulary::english();
+        let input = vec![
+            "été1ñ2ü".to_string(),
+            "🎄seven→".into(),
+            "ünine日本8".into(),
+            "ééétwoö".into(),
+        ];
+        assert!(process(&input, &vocabulary).unwrap() == 12 + 77 + 98 + 22);
+        assert!(process(&["日本語".to_string()], &vocabulary).is_err());
+
+        let vocabulary = Vocabulary::parse("один = 1\nдва = 2\né = 5").unwrap();
+        let input = vec!["одиндва".to_string(), "xдваé".into(), "é3".into()];
+        assert!(process(&input, &vocabulary).unwrap() == 12 + 25 + 53);
+    }
+
+    #[test]
+    fn test6() {
+        let vocabulary = Vocabulary::english();
+        let input = vec![
+            "xtwone3four".to_string(),
+            "treb7uchet".into(),
+            "été".into(),
+            "sixé".into(),
+        ];
+        let lines = explain(&input, &vocabulary);
+        assert!(lines[0] == "   1: 24 (first `two` 1..4 word, last `four` 7..11 word)");
+        assert!(lines[1] == "   2: 77 (first `7` 4..5 digit, last `7` 4..5 digit)");
+        assert!(lines[2] == "   3: NO DIGIT in \"été\"");
+        assert!(lines[3] == "   4: 66 (first `six` 0..3 word, last `six` 0..3 word)");
+    }
+
+    #[test]
+    fn test7() {
+        let input = "two1nine\r\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2\nzoneight234\n7pqrstsixteen";
+        let mut reports = Vec::new();
+        let sum = process_stream(input.as_bytes(), 3, |p| {
+            reports.push((p.lines, p.bytes, p.sum.to_string()))
+        })
+        .unwrap();
+        assert!(sum == 281);
+        assert!(
+            reports
+                == [
+                    (3, 39, "125".to_string()),
+                    (6, 80, "205".into()),
+                    (7, 93, "281".into())
+                ]
+        );
+
+        let err = process_stream("1\n2\nabc\n3\n".as_bytes(), 0, |_| {}).unwrap_err();
+        assert!(err.to_string().contains("day01:3:1"));
+
+        let mut reports = 0;
+        process_stream("1\n2\n".as_bytes(), 2, |_| reports += 1).unwrap();
+        assert!(reports == 1);
     }
 }
@@ -1,109 +1,518 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{fmt, fs, path::Path};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use num::BigUint;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use serde::Serialize;
 
-use crate::lines_from_file;
+use crate::{
+    arith::{self, Answer},
+    error::{parse_token, ParseError},
+    registry::{Implementation, Solution},
+    setting::Setting,
+};
 
-fn parse_game(line: &str) -> Result<i32> {
-    const MAX_RED: i32 = 12;
-    const MAX_GREEN: i32 = 13;
-    const MAX_BLUE: i32 = 14;
+const DAY: u32 = 2;
 
-    let mut possible = true;
-    let tokens: Vec<&str> = line.split(' ').collect();
-    let mut chunks = tokens.chunks(2);
+/// Cubes in the bag, 12 red, 13 green and 14 blue unless set from the command line.
+pub static BAG: Setting<Bag> = Setting::new("bag", Bag::default);
 
-    // check first chunk to get game index
-    let id = chunks.next().unwrap()[1].replace(':', "");
+/// Cubes of one color shown at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Draw<'a> {
+    pub count: u32,
+    pub color: &'a str,
+}
+
+/// Handful of cubes shown before putting them back in the bag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Round<'a> {
+    pub draws: Vec<Draw<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game<'a> {
+    pub id: u32,
+    pub rounds: Vec<Round<'a>>,
+}
+
+/// Parse `<count> <color>`, a slice of `line`.
+fn parse_draw<'a>(index: usize, line: &str, draw: &'a str) -> Result<Draw<'a>, ParseError> {
+    let draw = draw.trim();
+    let Some((count, color)) = draw.split_once(' ') else {
+        return Err(ParseError::at(
+            DAY,
+            index,
+            line,
+            draw,
+            "missing color after count",
+        ));
+    };
+    let count = parse_token(DAY, index, line, count)?;
+    let color = color.trim();
+    if color.is_empty() || !color.chars().all(char::is_alphabetic) {
+        return Err(ParseError::at(DAY, index, line, color, "invalid color"));
+    }
+    Ok(Draw { count, color })
+}
+
+/// Parse `Game <id>: <draw>, <draw>; <draw>...`, keeping the rounds apart.
+fn parse_game(index: usize, line: &str) -> Result<Game<'_>, ParseError> {
+    let Some((id, rounds)) = line
+        .strip_prefix("Game ")
+        .and_then(|rest| rest.split_once(':'))
+    else {
+        return Err(ParseError::line(DAY, index, line, "expected `Game <id>:`"));
+    };
+    let id = parse_token(DAY, index, line, id)?;
+    let rounds = rounds
+        .split(';')
+        .map(|round| {
+            let draws = round
+                .split(',')
+                .map(|draw| parse_draw(index, line, draw))
+                .collect::<Result<Vec<Draw>, ParseError>>()?;
+            Ok(Round { draws })
+        })
+        .collect::<Result<Vec<Round>, ParseError>>()?;
+    Ok(Game { id, rounds })
+}
+
+impl<'a> Game<'a> {
+    pub fn draws(&self) -> impl Iterator<Item = &Draw<'a>> {
+        self.rounds.iter().flat_map(|round| &round.draws)
+    }
+
+    /// Fewest cubes of each color drawn making the game possible.
+    pub fn minimal_bag(&self) -> Bag {
+        minimal_bag([self])
+    }
+}
+
+/// Fewest cubes of each color drawn making all the games possible, colors
+/// ordered by first appearance.
+pub fn minimal_bag<'a, 'b: 'a>(games: impl IntoIterator<Item = &'a Game<'b>>) -> Bag {
+    let mut cubes: Vec<(String, u32)> = Vec::new();
+    for draw in games.into_iter().flat_map(Game::draws) {
+        match cubes.iter_mut().find(|(color, _)| color == draw.color) {
+            Some((_, count)) => *count = (*count).max(draw.count),
+            None => cubes.push((draw.color.to_string(), draw.count)),
+        }
+    }
+    Bag { cubes }
+}
+
+/// Number of cubes of each color in the bag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bag {
+    pub cubes: Vec<(String, u32)>,
+}
+
+impl Default for Bag {
+    fn default() -> Self {
+        Bag {
+            cubes: vec![
+                ("red".into(), 12),
+                ("green".into(), 13),
+                ("blue".into(), 14),
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cubes = self
+            .cubes
+            .iter()
+            .map(|(color, count)| format!("{} {}", count, color));
+        write!(f, "{}", cubes.collect::<Vec<String>>().join(", "))
+    }
+}
 
-    // iterate next chunks and check whether game is possible
-    for elem in chunks {
-        let n: i32 = FromStr::from_str(elem[0])?;
-        let color = elem[1].replace([',', ';'], "");
+impl Bag {
+    /// Read `<count> <color>` items separated by commas or new lines.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut cubes: Vec<(String, u32)> = Vec::new();
+        for item in text
+            .split([',', '\n'])
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let draw =
+                parse_draw(0, item, item).map_err(|e| anyhow!("{}: `{}`", e.message, item))?;
+            if cubes.iter().any(|(color, _)| color == draw.color) {
+                bail!("color `{}` given twice", draw.color);
+            }
+            cubes.push((draw.color.to_string(), draw.count));
+        }
+        if cubes.is_empty() {
+            bail!("empty bag");
+        }
+        Ok(Bag { cubes })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        Bag::parse(&text).with_context(|| format!("invalid bag in {}", path.display()))
+    }
+
+    /// Number of cubes of `color`, `None` for colors not in the bag.
+    pub fn count(&self, color: &str) -> Option<u32> {
+        self.cubes
+            .iter()
+            .find(|(c, _)| c == color)
+            .map(|(_, count)| *count)
+    }
+
+    /// Whether every round of the game can be drawn from this bag.
+    pub fn allows(&self, game: &Game) -> bool {
+        game.draws()
+            .all(|d| self.count(d.color).is_some_and(|count| d.count <= count))
+    }
 
-        if color.eq("blue") && n > MAX_BLUE {
-            possible = false;
-            break;
+    /// Colors of which the game draws more cubes than the bag holds.
+    pub fn exceeded<'a>(&self, game: &Game<'a>) -> Vec<&'a str> {
+        let mut colors = Vec::new();
+        for draw in game.draws() {
+            if self
+                .count(draw.color)
+                .is_none_or(|count| draw.count > count)
+                && !colors.contains(&draw.color)
+            {
+                colors.push(draw.color);
+            }
         }
+        colors
+    }
+}
+
+/// Number of bags holding exactly `total` cubes, of the colors of `minimum` and
+/// at least as many of each: the spare cubes spread over the colors.
+pub fn count_bags(minimum: &Bag, total: u32) -> BigUint {
+    let required = minimum
+        .cubes
+        .iter()
+        .map(|(_, count)| *count as u64)
+        .sum::<u64>();
+    let Some(spare) = (total as u64).checked_sub(required) else {
+        return BigUint::from(0u32);
+    };
+    // choose(spare + colors - 1, colors - 1)
+    let colors = minimum.cubes.len() as u64;
+    if colors == 0 {
+        return BigUint::from((spare == 0) as u32);
+    }
+    (1..colors).fold(BigUint::from(1u32), |acc, i| acc * (spare + i) / i)
+}
 
-        if color.eq("green") && n > MAX_GREEN {
-            possible = false;
-            break;
+/// First `limit` bags counted by [`count_bags`], in lexicographic order of counts.
+pub fn bags(minimum: &Bag, total: u32, limit: usize) -> Vec<Bag> {
+    fn fill(minimum: &Bag, spare: u32, counts: &mut Vec<u32>, bags: &mut Vec<Bag>, limit: usize) {
+        if bags.len() >= limit {
+            return;
         }
+        let (_, min) = &minimum.cubes[counts.len()];
+        if counts.len() + 1 == minimum.cubes.len() {
+            counts.push(min + spare);
+            let cubes = minimum
+                .cubes
+                .iter()
+                .zip(counts.iter())
+                .map(|((c, _), n)| (c.clone(), *n));
+            bags.push(Bag {
+                cubes: cubes.collect(),
+            });
+            counts.pop();
+            return;
+        }
+        for extra in 0..=spare {
+            if bags.len() >= limit {
+                break;
+            }
+            counts.push(min + extra);
+            fill(minimum, spare - extra, counts, bags, limit);
+            counts.pop();
+        }
+    }
 
-        if color.eq("red") && n > MAX_RED {
-            possible = false;
-            break;
+    let required = minimum
+        .cubes
+        .iter()
+        .map(|(_, count)| *count as u64)
+        .sum::<u64>();
+    let mut bags = Vec::new();
+    match (total as u64).checked_sub(required) {
+        Some(spare) if !minimum.cubes.is_empty() => {
+            fill(minimum, spare as u32, &mut Vec::new(), &mut bags, limit)
         }
+        Some(0) if limit > 0 => bags.push(Bag { cubes: Vec::new() }),
+        _ => {}
+    }
+    bags
+}
+
+fn parse_games(input: &[String]) -> Result<Vec<Game<'_>>> {
+    Ok(input
+        .iter()
+        .enumerate()
+        .map(|(i, line)| parse_game(i, line))
+        .collect::<Result<Vec<Game>, ParseError>>()?)
+}
+
+/// Fewest cubes making each game possible, then all of them.
+pub fn show_bags(input: &[String]) -> Result<String> {
+    let games = parse_games(input)?;
+    let mut lines = games
+        .iter()
+        .map(|game| format!("game {}: {}", game.id, game.minimal_bag()))
+        .collect::<Vec<String>>();
+    lines.push(format!("all games: {}", minimal_bag(&games)));
+    Ok(lines.join("\n"))
+}
+
+/// Bags of `total` cubes making the games with the given ids possible, all games
+/// when none is given, listing up to `limit` of them.
+pub fn infer(input: &[String], total: u32, ids: &[u32], limit: usize) -> Result<Vec<String>> {
+    let games = parse_games(input)?;
+    if let Some(id) = ids
+        .iter()
+        .find(|id| !games.iter().any(|game| game.id == **id))
+    {
+        bail!("no game {}", id);
     }
+    let chosen = games
+        .iter()
+        .filter(|game| ids.is_empty() || ids.contains(&game.id));
 
-    match possible {
-        true => Ok(id.parse()?),
-        false => Ok(0),
+    // colors never drawn in the chosen games may still be in the bag
+    let mut minimum = minimal_bag(&games);
+    let required = minimal_bag(chosen);
+    for (color, count) in minimum.cubes.iter_mut() {
+        *count = required.count(color).unwrap_or(0);
     }
+
+    let mut lines = vec![format!(
+        "{} bags of {} cubes, with at least {}",
+        count_bags(&minimum, total),
+        total,
+        minimum
+    )];
+    lines.extend(
+        bags(&minimum, total, limit)
+            .iter()
+            .map(|bag| format!(" - {}", bag)),
+    );
+    Ok(lines)
 }
 
-fn parse_game_power(line: &str) -> Result<i32> {
-    let tokens: Vec<&str> = line.split(' ').collect();
-    let mut chunks = tokens.chunks(2);
-    let mut color_map: HashMap<&str, i32> = [("blue", 0), ("green", 0), ("red", 0)].into();
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ColorStats {
+    pub color: String,
+    /// Number of rounds drawing the color
+    pub draws: usize,
+    pub max: u32,
+    pub mean: f64,
+    /// Cubes of the color in the bag
+    pub limit: Option<u32>,
+    /// Number of games drawing more cubes of the color than the bag holds
+    pub impossible: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ImpossibleGame {
+    pub id: u32,
+    /// Colors exceeding the bag
+    pub colors: Vec<String>,
+}
 
-    // skip first chunk containing game id
-    chunks.next();
+/// Summary of all games against a bag.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Stats {
+    pub games: usize,
+    pub min_rounds: usize,
+    pub mean_rounds: f64,
+    pub max_rounds: usize,
+    pub colors: Vec<ColorStats>,
+    pub impossible: Vec<ImpossibleGame>,
+}
 
-    // iterate next chunks
-    for elem in chunks {
-        let n: i32 = FromStr::from_str(elem[0])?;
-        let color = elem[1].replace([',',';'], "").to_string();
+pub fn stats(input: &[String], bag: &Bag) -> Result<Stats> {
+    let games = parse_games(input)?;
+    let impossible = games
+        .iter()
+        .map(|game| (game.id, bag.exceeded(game)))
+        .filter(|(_, colors)| !colors.is_empty())
+        .map(|(id, colors)| ImpossibleGame {
+            id,
+            colors: colors.into_iter().map(String::from).collect(),
+        })
+        .collect::<Vec<ImpossibleGame>>();
 
-        // replace value in color_map if greater than existing
-        if let Some(value) = color_map.get_mut(color.as_str()) {
-            if n > *value {
-                *value = n;
+    // colors of the bag first, then the unknown ones
+    let mut names = bag
+        .cubes
+        .iter()
+        .map(|(color, _)| color.clone())
+        .collect::<Vec<String>>();
+    for (color, _) in minimal_bag(&games).cubes {
+        if !names.contains(&color) {
+            names.push(color);
+        }
+    }
+    let colors = names
+        .into_iter()
+        .map(|color| {
+            let counts = games
+                .iter()
+                .flat_map(Game::draws)
+                .filter(|d| d.color == color)
+                .map(|d| d.count);
+            let (draws, sum, max) = counts.fold((0, 0u64, 0), |(n, sum, max), c| {
+                (n + 1, sum + c as u64, max.max(c))
+            });
+            ColorStats {
+                draws,
+                max,
+                mean: if draws == 0 {
+                    0.0
+                } else {
+                    sum as f64 / draws as f64
+                },
+                limit: bag.count(&color),
+                impossible: impossible
+                    .iter()
+                    .filter(|game| game.colors.contains(&color))
+                    .count(),
+                color,
             }
+        })
+        .collect();
+
+    let rounds = games.iter().map(|game| game.rounds.len());
+    Ok(Stats {
+        games: games.len(),
+        min_rounds: rounds.clone().min().unwrap_or(0),
+        mean_rounds: rounds.clone().sum::<usize>() as f64 / games.len().max(1) as f64,
+        max_rounds: rounds.max().unwrap_or(0),
+        colors,
+        impossible,
+    })
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} games, rounds per game: min {}, mean {:.2}, max {}",
+            self.games, self.min_rounds, self.mean_rounds, self.max_rounds
+        )?;
+        writeln!(
+            f,
+            "{:<10} {:>6} {:>5} {:>7} {:>6} {:>11}",
+            "color", "draws", "max", "mean", "limit", "impossible"
+        )?;
+        for c in &self.colors {
+            let limit = c.limit.map_or("-".to_string(), |limit| limit.to_string());
+            writeln!(
+                f,
+                "{:<10} {:>6} {:>5} {:>7.2} {:>6} {:>11}",
+                c.color, c.draws, c.max, c.mean, limit, c.impossible
+            )?;
         }
+        write!(f, "{} impossible games", self.impossible.len())?;
+        for game in &self.impossible {
+            write!(f, "\n - game {}: {}", game.id, game.colors.join(", "))?;
+        }
+        Ok(())
     }
+}
 
+/// Product of the fewest cubes of each color drawn making the game possible.
+fn power(game: &Game) -> Result<u32> {
     let mut power = 1;
-    for (_, value) in color_map {
-        power *= value;
+    for (_, count) in &game.minimal_bag().cubes {
+        power = arith::mul(power, *count)?;
     }
-
     Ok(power)
 }
 
-fn process(input: Vec<String>) -> Result<i32> {
-    let mut add = 0;
-    for line in input {
-        match parse_game(line.as_str()) {
-            Ok(res) => add += res,
-            Err(_) => bail!("Failed to parse line"),
+fn process(input: &[String], bag: &Bag) -> Result<Answer> {
+    let mut add = Answer::zero();
+    for (i, line) in input.iter().enumerate() {
+        let game = parse_game(i, line)?;
+        if bag.allows(&game) {
+            add.add(game.id)?;
         }
     }
     Ok(add)
 }
 
-fn process_power(input: Vec<String>) -> Result<i32> {
-    let mut add = 0;
-    for line in input {
-        match parse_game_power(line.as_str()) {
-            Ok(res) => add += res,
-            Err(_) => bail!("Failed to parse line"),
-        }
+fn process_power(input: &[String]) -> Result<Answer> {
+    let mut add = Answer::zero();
+    for (i, line) in input.iter().enumerate() {
+        add.add(power(&parse_game(i, line)?)?)?;
     }
     Ok(add)
 }
 
-pub fn run() {
-    let res = process(lines_from_file("./input/day02.txt"));
-    println!(" * {:?}", res.unwrap());
-    let res = process_power(lines_from_file("./input/day02.txt"));
-    println!(" * {:?}", res.unwrap());
+/// Syntax errors. A color missing from the bag only makes its games impossible.
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| parse_game(i, line).err())
+        .collect()
+}
+
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    (1..=size)
+        .map(|id| {
+            let rounds = (0..rng.gen_range(1..6))
+                .map(|_| {
+                    let mut colors = vec!["red", "green", "blue"];
+                    colors.shuffle(rng);
+                    colors.truncate(rng.gen_range(1..=3));
+                    colors
+                        .iter()
+                        .map(|color| format!("{} {}", rng.gen_range(1..=20), color))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                })
+                .collect::<Vec<String>>();
+            format!("Game {}: {}", id, rounds.join("; "))
+        })
+        .collect()
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse: |input| parse_games(input).map(|_| ()),
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", |input| {
+                process(input, BAG.get())
+            })],
+            vec![Implementation::new("default", process_power)],
+        ],
+        inspectors: vec![],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day02::{process, process_power};
+    use crate::{
+        day02::{
+            bags, count_bags, infer, parse_game, process, process_power, show_bags, stats,
+            validate, Bag, Draw,
+        },
+        error::ParseError,
+    };
 
     #[test]
     fn test1() {
@@ -114,7 +523,7 @@ mod tests {
             "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red".into(),
             "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green".into(),
         ];
-        assert!(process(input).unwrap() == 8);
+        assert!(process(&input, &Bag::default()).unwrap() == 8);
     }
 
     #[test]
@@ -126,6 +535,151 @@ mod tests {
             "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red".into(),
             "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green".into(),
         ];
-        assert!(process_power(input).unwrap() == 2286);
+        assert!(process_power(&input).unwrap() == 2286);
+    }
+
+    #[test]
+    fn test3() {
+        let input = vec![
+            "Game 1: 3 blue, 4 red".to_string(),
+            "Game 2: 1 blue, x green".into(),
+        ];
+        let err = process(&input, &Bag::default())
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert!(err.line == 2 && err.column == 17 && err.text == "x");
+    }
+
+    #[test]
+    fn test4() {
+        let game = parse_game(0, "Game 7: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        assert!(game.id == 7 && game.rounds.len() == 3);
+        assert!(
+            game.rounds[1].draws[2]
+                == Draw {
+                    count: 6,
+                    color: "blue"
+                }
+        );
+        let bag = game.minimal_bag();
+        assert!(bag.count("blue") == Some(6) && bag.count("yellow").is_none());
+
+        assert!(
+            parse_game(0, "Game 1: 3 blue,").unwrap_err().message == "missing color after count"
+        );
+        assert!(parse_game(0, "Game 1: 3 blue 4 red").unwrap_err().message == "invalid color");
+        assert!(parse_game(0, "Game one: 3 blue").unwrap_err().text == "one");
+    }
+
+    #[test]
+    fn test5() {
+        let input = vec![
+            "Game 1: 3 cyan, 4 magenta; 1 yellow".to_string(),
+            "Game 2: 5 cyan; 2 yellow, 2 magenta".into(),
+            "Game 3: 1 cyan, 1 red".into(),
+        ];
+        let bag = Bag::parse("4 cyan, 4 magenta\n2 yellow").unwrap();
+        assert!(bag.count("yellow") == Some(2) && bag.count("red").is_none());
+        assert!(process(&input, &bag).unwrap() == 1);
+        // the bag only decides which games are possible, not their power
+        assert!(process_power(&input).unwrap() == 12 + 20 + 1);
+        assert!(validate(&input).is_empty());
+
+        assert!(Bag::parse("4 cyan, 2 cyan").is_err());
+        assert!(Bag::parse("cyan 4").is_err());
+        assert!(Bag::parse(" ,\n").is_err());
+    }
+
+    #[test]
+    fn test6() {
+        let input = vec![
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green".to_string(),
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue".into(),
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red".into(),
+        ];
+        let text = show_bags(&input).unwrap();
+        assert!(text == "game 1: 6 blue, 4 red, 2 green\ngame 2: 4 blue, 3 green, 1 red\ngame 3: 13 green, 6 blue, 20 red\nall games: 6 blue, 20 red, 13 green");
+
+        let lines = infer(&input, 15, &[1, 2], 3).unwrap();
+        assert!(lines[0] == "6 bags of 15 cubes, with at least 6 blue, 4 red, 3 green");
+        assert!(
+            lines[1..]
+                == [
+                    " - 6 blue, 4 red, 5 green",
+                    " - 6 blue, 5 red, 4 green",
+                    " - 6 blue, 6 red, 3 green"
+                ]
+        );
+        assert!(
+            infer(&input, 38, &[], 10).unwrap()
+                == ["0 bags of 38 cubes, with at least 6 blue, 20 red, 13 green"]
+        );
+        assert!(infer(&input, 15, &[4], 3).is_err());
+    }
+
+    #[test]
+    fn test7() {
+        // counting agrees with enumerating
+        let minimum = Bag::parse("1 red, 0 green, 2 blue, 3 yellow").unwrap();
+        for total in 0..12 {
+            let bags = bags(&minimum, total, usize::MAX);
+            assert!(count_bags(&minimum, total) == bags.len().into());
+            assert!(bags
+                .iter()
+                .all(|bag| bag.cubes.iter().map(|(_, n)| n).sum::<u32>() == total));
+        }
+        assert!(count_bags(&minimum, 106) == 176851u32.into());
+
+        // listing stops as soon as enough bags are found
+        let bags = bags(&minimum, u32::MAX, 2);
+        assert!(
+            bags.len() == 2 && bags[1].to_string() == "1 red, 0 green, 3 blue, 4294967291 yellow"
+        );
+    }
+
+    #[test]
+    fn test8() {
+        let input = vec![
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green".to_string(),
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red".into(),
+            "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red".into(),
+            "Game 5: 1 cyan".into(),
+        ];
+        let stats = stats(&input, &Bag::default()).unwrap();
+        assert!(stats.games == 4 && stats.min_rounds == 1 && stats.max_rounds == 3);
+        assert!(
+            stats
+                .colors
+                .iter()
+                .map(|c| c.color.as_str())
+                .collect::<Vec<&str>>()
+                == ["red", "green", "blue", "cyan"]
+        );
+        assert!(
+            stats.colors[0].draws == 8
+                && stats.colors[0].max == 20
+                && stats.colors[0].impossible == 2
+        );
+        assert!(stats.colors[3].limit.is_none() && stats.colors[3].impossible == 1);
+        assert!(
+            stats
+                .impossible
+                .iter()
+                .map(|g| (g.id, g.colors.join(" ")))
+                .collect::<Vec<_>>()
+                == [
+                    (3, "red".to_string()),
+                    (4, "blue red".into()),
+                    (5, "cyan".into()),
+                ]
+        );
+
+        let text = stats.to_string();
+        assert!(text.starts_with("4 games, rounds per game: min 1, mean 2.50, max 3\n"));
+        assert!(text.contains("\nblue            6    15    6.83     14           1\n"));
+        assert!(text.ends_with(
+            "3 impossible games\n - game 3: red\n - game 4: blue, red\n - game 5: cyan"
+        ));
     }
 }
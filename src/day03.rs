@@ -1,147 +1,315 @@
-use std::cmp;
-
-use atoi::FromRadix10;
-
-use anyhow::Result;
-use multimap::MultiMap;
-
-use crate::lines_from_file;
-
-fn is_adjacent_to_symbol(
-    prev: Option<&String>,
-    cur: Option<&String>,
-    next: Option<&String>,
-    pos: i32,
-    len: i32,
-) -> Option<(char, i32, i32)> {
-    if let Some(line) = prev {
-        let min = cmp::max(pos - 1, 0) as usize;
-        let max = cmp::min(pos + len + 1, line.len() as i32) as usize;
-        for (i, c) in line[min..max].chars().enumerate() {
-            if !c.is_ascii_digit() && c != '.' {
-                return Some((c, (i + min) as i32, -1));
-            }
-        }
-    };
-
-    if let Some(line) = next {
-        let min = cmp::max(pos - 1, 0) as usize;
-        let max = cmp::min(pos + len + 1, line.len() as i32) as usize;
-        for (i, c) in line[min..max].chars().enumerate() {
-            if !c.is_ascii_digit() && c != '.' {
-                return Some((c, (i + min) as i32, 1));
-            }
-        }
-    };
+use std::{collections::HashMap, ops::Range, str::FromStr};
+
+use anyhow::{anyhow, bail, Result};
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    arith::{self, Answer},
+    error::{parse_token, ParseError},
+    registry::{Implementation, Inspector, Solution},
+    setting::Setting,
+    validate,
+};
+
+const DAY: u32 = 3;
+
+/// Which symbols are gears and how their ratio is computed, `*:2:product` unless
+/// set from the command line.
+pub static GEAR_RULE: Setting<GearRule> = Setting::new("gear rule", GearRule::default);
+
+/// Number of the schematic, along with the symbols around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: u32,
+    pub line: usize,
+    /// Columns of the digits, in characters
+    pub columns: Range<usize>,
+    /// Indices of the adjacent symbols, in reading order
+    pub symbols: Vec<usize>,
+}
 
-    if let Some(line) = cur {
-        if pos > 0 {
-            let c = line.chars().nth(pos as usize - 1).unwrap();
-            if !c.is_ascii_digit() && c != '.' {
-                return Some((c, pos - 1, 0));
+/// Any character other than a digit or `.`, along with the numbers around it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub symbol: char,
+    pub line: usize,
+    pub column: usize,
+    /// Indices of the adjacent numbers, in reading order
+    pub numbers: Vec<usize>,
+}
+
+/// Engine schematic, with every adjacency between numbers and symbols,
+/// diagonals included.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Schematic {
+    pub numbers: Vec<Number>,
+    pub symbols: Vec<Symbol>,
+}
+
+impl Schematic {
+    /// Lines may have any length, including none: cells past the end of a line
+    /// are empty, so an empty input is a schematic without numbers.
+    pub fn parse(input: &[String]) -> Result<Self, ParseError> {
+        let mut schematic = Schematic::default();
+        let mut positions = HashMap::new();
+
+        for (i, line) in input.iter().enumerate() {
+            let mut chars = line.char_indices().enumerate().peekable();
+            while let Some((column, (start, c))) = chars.next() {
+                if c.is_ascii_digit() {
+                    let mut end = (column + 1, start + 1);
+                    while let Some((column, (x, _))) =
+                        chars.next_if(|(_, (_, c))| c.is_ascii_digit())
+                    {
+                        end = (column + 1, x + 1);
+                    }
+                    schematic.numbers.push(Number {
+                        value: parse_token(DAY, i, line, &line[start..end.1])?,
+                        line: i,
+                        columns: column..end.0,
+                        symbols: Vec::new(),
+                    });
+                } else if c != '.' {
+                    positions.insert((i, column), schematic.symbols.len());
+                    schematic.symbols.push(Symbol {
+                        symbol: c,
+                        line: i,
+                        column,
+                        numbers: Vec::new(),
+                    });
+                }
             }
         }
-        if pos + len < line.len() as i32 {
-            let c = line.chars().nth((pos + len) as usize).unwrap();
-            if !c.is_ascii_digit() && c != '.' {
-                return Some((c, pos + len, 0));
+
+        for (n, number) in schematic.numbers.iter_mut().enumerate() {
+            for line in number.line.saturating_sub(1)..=number.line + 1 {
+                for column in number.columns.start.saturating_sub(1)..=number.columns.end {
+                    if let Some(&s) = positions.get(&(line, column)) {
+                        number.symbols.push(s);
+                        schematic.symbols[s].numbers.push(n);
+                    }
+                }
             }
         }
-    };
-
-    None
-}
-
-fn find_engine_part(
-    engine_map: &mut MultiMap<String, i32>,
-    prev_line: Option<&String>,
-    cur_line: Option<&String>,
-    next_line: Option<&String>,
-    nline: i32,
-) -> Result<()> {
-    let mut i = 0;
-    loop {
-        let slice = &cur_line.unwrap()[i..];
-        let (num, len) = i32::from_radix_10(slice.as_bytes());
-        if len > 0 {
-            if let Some((c, p, m)) = is_adjacent_to_symbol(prev_line, cur_line, next_line, i as i32, len as i32) {
-                engine_map.insert(format!("{} {} {}", c, nline + m, p), num)
-            };
-            i += len;
-        } else {
-            i += 1;
-        }
+        Ok(schematic)
+    }
 
-        if i >= cur_line.unwrap().len() {
-            break;
-        }
+    /// Numbers adjacent to at least one symbol.
+    pub fn parts(&self) -> impl Iterator<Item = &Number> {
+        self.numbers.iter().filter(|n| !n.symbols.is_empty())
+    }
+
+    /// Numbers adjacent to a symbol.
+    pub fn neighbors<'a>(&'a self, symbol: &'a Symbol) -> impl Iterator<Item = &'a Number> {
+        symbol.numbers.iter().map(|&n| &self.numbers[n])
+    }
+
+    /// Symbols selected by the rule.
+    pub fn gears<'a>(&'a self, rule: &'a GearRule) -> impl Iterator<Item = &'a Symbol> {
+        self.symbols.iter().filter(|s| rule.matches(s))
     }
-    Ok(())
 }
 
-fn parse_engine(input: Vec<String>) -> Result<MultiMap<String, i32>> {
-    let mut engine_map: MultiMap<String, i32> = MultiMap::new();
+/// Number of adjacent numbers a gear must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Count {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+}
 
-    let mut line_idx = 0;
+/// How the numbers around a gear combine into its ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Product,
+    Max,
+}
 
-    // first line
-    find_engine_part(&mut engine_map, None, Some(&input[0]), Some(&input[1]), 0)?;
+/// Which symbols are gears, and how their ratio is computed, written
+/// `<symbols>:<count>:<aggregate>`, such as `*:2:product` or `#$:>=3:sum`.
+/// Symbols may be `any`, counts are `N`, `=N`, `>=N` or `<=N`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    /// Symbols that can be gears, any of them when `None`
+    pub symbols: Option<Vec<char>>,
+    pub count: Count,
+    pub aggregate: Aggregate,
+}
 
-    // main loop
-    for lines in input[..].windows(3) {
-        find_engine_part(
-            &mut engine_map,
-            Some(&lines[0]),
-            Some(&lines[1]),
-            Some(&lines[2]),
-            line_idx + 1,
-        )?;
-        line_idx += 1;
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbols: Some(vec!['*']),
+            count: Count::Exactly(2),
+            aggregate: Aggregate::Product,
+        }
     }
+}
 
-    // last line
-    find_engine_part(
-        &mut engine_map,
-        Some(&input[line_idx as usize]),
-        Some(&input[line_idx as usize + 1]),
-        None,
-        line_idx + 1,
-    )?;
+impl FromStr for GearRule {
+    type Err = anyhow::Error;
 
-    Ok(engine_map)
+    fn from_str(s: &str) -> Result<Self> {
+        // split from the end, so that `:` can be a symbol
+        let [aggregate, count, symbols] = s.rsplitn(3, ':').collect::<Vec<&str>>()[..] else {
+            bail!("expected `<symbols>:<count>:<aggregate>`");
+        };
+        let symbols = match symbols {
+            "" => bail!("no symbols"),
+            "any" => None,
+            symbols => Some(symbols.chars().collect()),
+        };
+        let number = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| anyhow!("invalid count `{}`", n))
+        };
+        let count = if let Some(n) = count.strip_prefix(">=") {
+            Count::AtLeast(number(n)?)
+        } else if let Some(n) = count.strip_prefix("<=") {
+            Count::AtMost(number(n)?)
+        } else {
+            Count::Exactly(number(count.strip_prefix('=').unwrap_or(count))?)
+        };
+        let aggregate = match aggregate {
+            "sum" => Aggregate::Sum,
+            "product" => Aggregate::Product,
+            "max" => Aggregate::Max,
+            _ => bail!(
+                "unknown aggregate `{}`, expected sum, product or max",
+                aggregate
+            ),
+        };
+        Ok(GearRule {
+            symbols,
+            count,
+            aggregate,
+        })
+    }
 }
 
-fn process1(input: Vec<String>) -> Result<i32> {
-    let engine_map = parse_engine(input)?;
-    let mut add = 0;
-    for (_, v) in engine_map.iter_all() {
-        add += v.iter().sum::<i32>();
+impl GearRule {
+    pub fn matches(&self, symbol: &Symbol) -> bool {
+        let count = symbol.numbers.len();
+        let symbols = self
+            .symbols
+            .as_ref()
+            .is_none_or(|symbols| symbols.contains(&symbol.symbol));
+        symbols
+            && match self.count {
+                Count::Exactly(n) => count == n,
+                Count::AtLeast(n) => count >= n,
+                Count::AtMost(n) => count <= n,
+            }
+    }
+
+    /// Ratio of a gear, from the values of its numbers.
+    pub fn ratio(&self, values: impl Iterator<Item = u32>) -> Result<u64> {
+        let mut values = values.map(u64::from);
+        Ok(match self.aggregate {
+            Aggregate::Sum => values.try_fold(0, arith::add)?,
+            Aggregate::Product => values.try_fold(1, arith::mul)?,
+            Aggregate::Max => values.max().unwrap_or(0),
+        })
+    }
+}
+
+fn process1(input: &[String]) -> Result<Answer> {
+    let schematic = Schematic::parse(input)?;
+    let mut add = Answer::zero();
+    for part in schematic.parts() {
+        add.add(part.value)?;
     }
     Ok(add)
 }
 
-fn process2(input: Vec<String>) -> Result<i32> {
-    let engine_map = parse_engine(input)?;
-    let mut gear = 0;
-    for (k, v) in engine_map.iter_all() {
-        if k.contains('*') && v.len() == 2 {
-            gear += v.iter().product::<i32>();
-        }
+fn process2(input: &[String], rule: &GearRule) -> Result<Answer> {
+    let schematic = Schematic::parse(input)?;
+    let mut gear = Answer::zero();
+    for symbol in schematic.gears(rule) {
+        gear.add(rule.ratio(schematic.neighbors(symbol).map(|n| n.value))?)?;
     }
     Ok(gear)
 }
 
-pub fn run() {
-    let res = process1(lines_from_file("./input/day03.txt"));
-    println!(" * {:?}", res.unwrap());
-    let res = process2(lines_from_file("./input/day03.txt"));
-    println!(" * {:?}", res.unwrap());
+/// Symbols along with their adjacent numbers, in reading order.
+fn show_engine(input: &[String]) -> Result<String> {
+    let schematic = Schematic::parse(input)?;
+    Ok(schematic
+        .symbols
+        .iter()
+        .filter(|s| !s.numbers.is_empty())
+        .map(|s| {
+            let numbers = schematic
+                .neighbors(s)
+                .map(|n| n.value)
+                .collect::<Vec<u32>>();
+            format!(
+                "{} at {}:{} <- {:?}",
+                s.symbol,
+                s.line + 1,
+                s.column + 1,
+                numbers
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+/// Ragged and empty schematics are fine, only the characters are checked.
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    validate::tiles(DAY, input, |c| c.is_ascii_graphic())
+}
+
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    const SYMBOLS: &[u8] = b"*#+$/@=%&-";
+
+    (0..size)
+        .map(|_| {
+            let mut line = vec![b'.'; size];
+            let mut x = 0;
+            while x < size {
+                match rng.gen_range(0..10) {
+                    0 => line[x] = SYMBOLS[rng.gen_range(0..SYMBOLS.len())],
+                    1 | 2 => {
+                        let number = rng.gen_range(1..1000).to_string();
+                        let len = number.len().min(size - x);
+                        line[x..x + len].copy_from_slice(&number.as_bytes()[..len]);
+                        // keep a gap so that numbers don't merge
+                        x += len;
+                    }
+                    _ => {}
+                }
+                x += 1;
+            }
+            String::from_utf8(line).unwrap()
+        })
+        .collect()
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse: |input| Ok(Schematic::parse(input).map(|_| ())?),
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", process1)],
+            vec![Implementation::new("default", |input| {
+                process2(input, GEAR_RULE.get())
+            })],
+        ],
+        inspectors: vec![Inspector {
+            name: "engine",
+            show: show_engine,
+        }],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day03::process1;
-    use crate::day03::process2;
+    use crate::day03::{process1, process2, validate, GearRule, Schematic};
 
     #[test]
     fn test1() {
@@ -157,7 +325,7 @@ mod tests {
             "...$.*....".into(),
             ".664.598..".into(),
         ];
-        assert!(process1(input).unwrap() == 4361);
+        assert!(process1(&input).unwrap() == 4361);
     }
 
     #[test]
@@ -174,6 +342,94 @@ mod tests {
             "...$.*....".into(),
             ".664.598..".into(),
         ];
-        assert!(process2(input).unwrap() == 467835);
+        assert!(process2(&input, &GearRule::default()).unwrap() == 467835);
+    }
+
+    #[test]
+    fn test3() {
+        // 12 touches both stars, 3 is not a part
+        let input = vec![
+            "*..*".to_string(),
+            ".12.".into(),
+            "...3".into(),
+            "7*5.".into(),
+        ];
+        let schematic = Schematic::parse(&input).unwrap();
+        assert!(schematic.numbers.len() == 4 && schematic.symbols.len() == 3);
+        assert!(schematic.numbers[0].columns == (1..3) && schematic.numbers[0].symbols == [0, 1]);
+        assert!(schematic.parts().map(|n| n.value).collect::<Vec<u32>>() == [12, 7, 5]);
+        assert!(schematic.symbols[2].numbers == [2, 3]);
+        assert!(
+            process1(&input).unwrap() == 24
+                && process2(&input, &GearRule::default()).unwrap() == 35
+        );
+    }
+
+    #[test]
+    fn test4() {
+        let input = vec![
+            "467..114..".to_string(),
+            "...*......".into(),
+            "..35..633.".into(),
+            "......#...".into(),
+            "617*......".into(),
+            ".....+.58.".into(),
+            "..592.....".into(),
+            "......755.".into(),
+            "...$.*....".into(),
+            ".664.598..".into(),
+        ];
+        let solve = |rule: &str| process2(&input, &rule.parse::<GearRule>().unwrap()).unwrap();
+        assert!(solve("*:=2:product") == 467835);
+        assert!(solve("*:>=1:sum") == 467 + 35 + 617 + 755 + 598);
+        assert!(solve("*:<=1:max") == 617);
+        assert!(solve("#$+:1:sum") == 633 + 664 + 592);
+        assert!(solve("any:2:max") == 467 + 755);
+
+        assert!("*:2:product".parse::<GearRule>().unwrap() == GearRule::default());
+        assert!("::1:sum".parse::<GearRule>().unwrap().symbols == Some(vec![':']));
+        assert!("*:2".parse::<GearRule>().is_err());
+        assert!("*:two:sum".parse::<GearRule>().is_err());
+        assert!("*:2:mean".parse::<GearRule>().is_err());
+        assert!(":2:sum".parse::<GearRule>().is_err());
+    }
+
+    #[test]
+    fn test5() {
+        let rule = GearRule::default();
+        let check = |input: &[&str], part1: i64, part2: i64| {
+            let input = input
+                .iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<String>>();
+            assert!(validate(&input).is_empty());
+            assert!(
+                process1(&input).unwrap() == part1 && process2(&input, &rule).unwrap() == part2
+            );
+        };
+        check(&[], 0, 0);
+        check(&[""], 0, 0);
+        check(&["12*3..4"], 15, 36);
+        check(&["7", "*"], 7, 0);
+        check(&["..5", "", "5.."], 0, 0);
+        check(&["", "3*2", ""], 5, 6);
+    }
+
+    #[test]
+    fn test6() {
+        // cells past the end of a short line are empty
+        let input = vec![
+            "10".to_string(),
+            "..*".into(),
+            "4.....".into(),
+            "...#.....99".into(),
+            ".".into(),
+        ];
+        let schematic = Schematic::parse(&input).unwrap();
+        assert!(schematic.parts().map(|n| n.value).collect::<Vec<u32>>() == [10]);
+        assert!(schematic.symbols[1].line == 3 && schematic.symbols[1].numbers.is_empty());
+        assert!(process1(&input).unwrap() == 10);
+        assert!(validate(&input).is_empty());
+        assert!(validate(&["1 *".to_string()]).len() == 1);
     }
 }
@@ -1,89 +1,226 @@
 use anyhow::Result;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
 
-use crate::lines_from_file;
+use crate::{
+    arith::{self, Answer, Overflow},
+    error::{parse_token, ParseError},
+    registry::{Implementation, Solution},
+};
 
-fn update_points(points: i32) -> i32 {
+const DAY: u32 = 4;
+
+struct Card {
+    index: usize,
+    winnings: Vec<i32>,
+    owns: Vec<i32>,
+}
+
+fn parse_card(index: usize, line: &str) -> Result<Card, ParseError> {
+    let Some((header, numbers)) = line.split_once(':') else {
+        return Err(ParseError::line(DAY, index, line, "expected `Card <id>:`"));
+    };
+    let card_index = match header.split_whitespace().collect::<Vec<&str>>()[..] {
+        ["Card", id] => parse_token::<usize>(DAY, index, line, id)?,
+        _ => {
+            return Err(ParseError::at(
+                DAY,
+                index,
+                line,
+                header,
+                "expected `Card <id>:`",
+            ))
+        }
+    };
+    let Some((winnings, owns)) = numbers.split_once('|') else {
+        return Err(ParseError::at(
+            DAY,
+            index,
+            line,
+            numbers,
+            "missing `|` separator",
+        ));
+    };
+
+    Ok(Card {
+        index: card_index,
+        winnings: winnings
+            .split_whitespace()
+            .map(|x| parse_token(DAY, index, line, x))
+            .collect::<Result<Vec<i32>, ParseError>>()?,
+        owns: owns
+            .split_whitespace()
+            .map(|x| parse_token(DAY, index, line, x))
+            .collect::<Result<Vec<i32>, ParseError>>()?,
+    })
+}
+
+fn update_points(points: i32) -> Result<i32, Overflow> {
     if points == 0 {
-        1
+        Ok(1)
     } else {
-        points * 2
+        arith::mul(points, 2)
     }
 }
 
-fn process(input: Vec<String>) -> Result<i32> {
-    let mut total = 0;
-    for lines in input {
+fn process(input: &[String]) -> Result<Answer> {
+    let mut total = Answer::zero();
+    for (i, lines) in input.iter().enumerate() {
         let mut points = 0;
+        let card = parse_card(i, lines)?;
 
-        let substr = &lines[lines.find(':').unwrap() + 2..];
-        let parts = substr.split('|').collect::<Vec<&str>>();
-
-        let winnings = parts[0]
-            .split_whitespace()
-            .map(|x| x.parse::<i32>().unwrap())
-            .collect::<Vec<i32>>();
-        let owns = parts[1]
-            .split_whitespace()
-            .map(|x| x.parse::<i32>().unwrap())
-            .collect::<Vec<i32>>();
-
-        for n in owns {
-            if winnings.contains(&n) {
-                points = update_points(points);
+        for n in card.owns {
+            if card.winnings.contains(&n) {
+                points = update_points(points)?;
             }
         }
 
-        total += points;
+        total.add(points)?;
     }
 
     Ok(total)
 }
 
-fn process2(input: Vec<String>) -> Result<i32> {
-    let mut map: Vec<i32> = vec![1; input.len()];
+/// Cards must be numbered in order, and must not win copies past the last one.
+fn card_violations(index: usize, line: &str, card: &Card, count: usize) -> Vec<ParseError> {
+    let mut violations = Vec::new();
+    if card.index != index + 1 {
+        violations.push(ParseError::line(
+            DAY,
+            index,
+            line,
+            format!("expected card {}", index + 1),
+        ));
+    }
 
-    for lines in input {
-        let card_index_str = lines.split_whitespace().nth(1).unwrap();
-        let card_index = card_index_str[..card_index_str.len() - 1]
-            .parse::<i32>()
-            .unwrap()
-            - 1;
+    let matches = card
+        .owns
+        .iter()
+        .filter(|n| card.winnings.contains(n))
+        .count();
+    if index + matches >= count {
+        violations.push(ParseError::line(
+            DAY,
+            index,
+            line,
+            format!("{} matches win copies past the last card", matches),
+        ));
+    }
+    violations
+}
 
-        let substr = &lines[lines.find(':').unwrap() + 2..];
-        let parts = substr.split('|').collect::<Vec<&str>>();
+fn process2(input: &[String]) -> Result<Answer> {
+    let mut map: Vec<i64> = vec![1; input.len()];
 
-        let winnings = parts[0]
-            .split_whitespace()
-            .map(|x| x.parse::<i32>().unwrap())
-            .collect::<Vec<i32>>();
-        let owns = parts[1]
-            .split_whitespace()
-            .map(|x| x.parse::<i32>().unwrap())
-            .collect::<Vec<i32>>();
-
-        let mut i = 0;
-        for n in owns {
-            if winnings.contains(&n) {
-                map[(card_index + i + 1) as usize] += map[card_index as usize];
-                i += 1;
-            }
+    for (i, lines) in input.iter().enumerate() {
+        let card = parse_card(i, lines)?;
+        if let Some(e) = card_violations(i, lines, &card, input.len())
+            .into_iter()
+            .next()
+        {
+            return Err(e.into());
+        }
+
+        let won = map[i];
+        let matches = card
+            .owns
+            .iter()
+            .filter(|n| card.winnings.contains(n))
+            .count();
+        for copies in &mut map[i + 1..=i + matches] {
+            *copies = arith::add(*copies, won)?;
         }
     }
 
-    Ok(map.iter().sum())
+    let mut total = Answer::zero();
+    for copies in map {
+        total.add(copies)?;
+    }
+    Ok(total)
 }
 
-pub fn run() {
-    let res = process(lines_from_file("./input/day04.txt"));
-    println!(" * {:?}", res.unwrap());
-    let res = process2(lines_from_file("./input/day04.txt"));
-    println!(" * {:?}", res.unwrap());
+fn parse(input: &[String]) -> Result<()> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, line)| parse_card(i, line))
+        .collect::<Result<Vec<Card>, ParseError>>()?;
+    Ok(())
+}
+
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    let mut violations = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        let card = match parse_card(i, line) {
+            Ok(card) => card,
+            Err(e) => {
+                violations.push(e);
+                continue;
+            }
+        };
+        violations.extend(card_violations(i, line, &card, input.len()));
+    }
+    violations
+}
+
+/// Generate `size` cards. Copies are won within runs of 25 cards, as each card
+/// winning copies of the next ones makes the totals grow exponentially along a
+/// run.
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    const RUN: usize = 25;
+    let format = |numbers: &[i32]| {
+        numbers
+            .iter()
+            .map(|n| format!("{:>2}", n))
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+
+    (0..size)
+        .map(|i| {
+            let mut numbers = (1..100).collect::<Vec<i32>>();
+            numbers.shuffle(rng);
+            let winnings = &numbers[..5];
+
+            // copies must not run past the last card of the run
+            let end = size.min((i / RUN + 1) * RUN);
+            let matches = rng.gen_range(0..=(end - i - 1).min(5));
+            let mut owns = winnings[..matches].to_vec();
+            owns.extend(&numbers[5..13 - matches]);
+            owns.shuffle(rng);
+
+            format!(
+                "Card {:>3}: {} | {}",
+                i + 1,
+                format(winnings),
+                format(&owns)
+            )
+        })
+        .collect()
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse,
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", process)],
+            vec![Implementation::new("default", process2)],
+        ],
+        inspectors: vec![],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::day04::generate;
     use crate::day04::process;
     use crate::day04::process2;
+    use crate::error::ParseError;
 
     #[test]
     fn test1() {
@@ -95,7 +232,7 @@ mod tests {
             "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36".into(),
             "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11".into(),
         ];
-        assert!(process(input).unwrap() == 13);
+        assert!(process(&input).unwrap() == 13);
     }
 
     #[test]
@@ -108,6 +245,32 @@ mod tests {
             "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36".into(),
             "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11".into(),
         ];
-        assert!(process2(input).unwrap() == 30);
+        assert!(process2(&input).unwrap() == 30);
+    }
+
+    #[test]
+    fn test3() {
+        let input = vec![
+            "Card 1: 41 48 | 41 48".to_string(),
+            "Card 2: 13 32 | 61 30".into(),
+        ];
+        let err = process2(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert!(err.line == 1 && err.message == "2 matches win copies past the last card");
+
+        let input = vec!["Card 0: 41 48 | 61 30".to_string()];
+        let err = process2(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert!(err.message == "expected card 1");
+    }
+
+    #[test]
+    fn test4() {
+        let input = generate(&mut StdRng::seed_from_u64(0), 1000);
+        assert!(process2(&input).unwrap().to_string().len() < 10);
     }
 }
@@ -1,6 +1,13 @@
 use anyhow::Result;
+use rand::{rngs::StdRng, Rng};
 
-use crate::lines_from_file;
+use crate::{
+    arith::{self, Answer},
+    error::{parse_token, ParseError},
+    registry::{Implementation, Inspector, Solution},
+};
+
+const DAY: u32 = 5;
 
 #[derive(Debug)]
 struct MyMap {
@@ -15,16 +22,45 @@ struct Segment {
     range: i64,
 }
 
-fn build_map(input: &Vec<String>) -> Result<Vec<MyMap>> {
+fn parse_seeds(index: usize, line: &str) -> Result<Vec<i64>, ParseError> {
+    line.split(':').collect::<Vec<&str>>()[1]
+        .split_whitespace()
+        .map(|x| parse_token(DAY, index, line, x))
+        .collect()
+}
+
+/// Index of the `seeds:` line and its seeds, of which there must be at least one.
+fn read_seeds(input: &[String]) -> Result<(usize, Vec<i64>), ParseError> {
+    let Some((i, line)) = input
+        .iter()
+        .enumerate()
+        .find(|(_, line)| line.contains("seeds:"))
+    else {
+        let first = input.first().map_or("", |l| l.as_str());
+        return Err(ParseError::line(DAY, 0, first, "missing `seeds:` line"));
+    };
+    let seeds = parse_seeds(i, line)?;
+    if seeds.is_empty() {
+        return Err(ParseError::line(DAY, i, line, "no seeds listed"));
+    }
+    Ok((i, seeds))
+}
+
+fn build_map(input: &[String], start: usize) -> Result<Vec<MyMap>> {
     let mut maps = Vec::new();
-    for lines in input {
-        if lines.eq("") {
+    for (i, lines) in input.iter().enumerate().skip(start) {
+        if lines.is_empty() {
             break;
         }
         let v = lines
             .split_whitespace()
-            .map(|x| x.parse::<i64>().unwrap())
-            .collect::<Vec<i64>>();
+            .map(|x| parse_token(DAY, i, lines, x))
+            .collect::<Result<Vec<i64>, ParseError>>()?;
+        if v.len() != 3 {
+            return Err(
+                ParseError::line(DAY, i, lines, "expected `<dest> <source> <range>`").into(),
+            );
+        }
 
         maps.push(MyMap {
             base: v[1],
@@ -36,14 +72,14 @@ fn build_map(input: &Vec<String>) -> Result<Vec<MyMap>> {
     Ok(maps)
 }
 
-fn process_seeds(src: Vec<i64>, maps: &Vec<MyMap>) -> Result<Vec<i64>> {
+fn process_seeds(src: Vec<i64>, maps: &[MyMap]) -> Result<Vec<i64>> {
     let mut new_seeds = Vec::new();
 
     for s in src {
         let mut found = false;
         for map in maps {
-            if s >= map.base && s <= map.base + map.range {
-                new_seeds.push(map.dest + s - map.base);
+            if s >= map.base && s <= arith::add(map.base, map.range)? {
+                new_seeds.push(arith::add(map.dest, arith::sub(s, map.base)?)?);
                 found = true;
             }
         }
@@ -54,23 +90,18 @@ fn process_seeds(src: Vec<i64>, maps: &Vec<MyMap>) -> Result<Vec<i64>> {
     Ok(new_seeds)
 }
 
-fn process(input: &Vec<String>) -> Result<i64> {
-    let mut seeds = Vec::new();
+fn process(input: &[String]) -> Result<Answer> {
+    let (_, mut seeds) = read_seeds(input)?;
     for (line_index, lines) in input.iter().enumerate() {
-        if lines.contains("seeds:") {
-            seeds = lines.split(':').collect::<Vec<&str>>()[1]
-                .split_whitespace()
-                .map(|x| x.parse::<i64>().unwrap())
-                .collect::<Vec<i64>>();
-        }
-
         if lines.contains("map:") {
-            let maps = build_map(&input[line_index + 1..].to_vec())?;
+            let maps = build_map(input, line_index + 1)?;
             seeds = process_seeds(seeds, &maps)?;
         }
     }
 
-    Ok(*seeds.iter().min().unwrap())
+    // each seed maps to at least one value
+    let min = seeds.into_iter().min().expect("seeds are not empty");
+    Ok(Answer::new(min)?)
 }
 
 fn process_map(segments: Vec<Segment>, maps_list: &[Vec<MyMap>]) -> Result<i64> {
@@ -78,8 +109,13 @@ fn process_map(segments: Vec<Segment>, maps_list: &[Vec<MyMap>]) -> Result<i64>
 
     // walk through all maps to update list of segments
     if maps_list.is_empty() {
-        let min = segments.iter().min_by_key(|x| x.base).unwrap();
-        return Ok(min.base);
+        // segments are split but never dropped, and there is at least one seed
+        let min = segments
+            .iter()
+            .map(|x| x.base)
+            .min()
+            .expect("segments are not empty");
+        return Ok(min);
     }
 
     for s in segments {
@@ -89,57 +125,61 @@ fn process_map(segments: Vec<Segment>, maps_list: &[Vec<MyMap>]) -> Result<i64>
                 break;
             }
 
-            if s.base >= m.base && s.base + s.range <= m.base + m.range {
+            let (s_end, m_end) = (arith::add(s.base, s.range)?, arith::add(m.base, m.range)?);
+            // position of a value of the source range in the destination range
+            let shift = |x: i64| arith::add(m.dest, arith::sub(x, m.base)?);
+
+            if s.base >= m.base && s_end <= m_end {
                 // segment entirely included in map
                 new_segments.push(Segment {
-                    base: m.dest + s.base - m.base,
+                    base: shift(s.base)?,
                     range: s.range,
                 });
                 found = true;
                 continue;
             }
 
-            if s.base < m.base && s.base + s.range > m.base + m.range {
+            if s.base < m.base && s_end > m_end {
                 // segment entirely overlapping map
                 new_segments.push(Segment {
                     base: s.base,
-                    range: m.base - s.base,
+                    range: arith::sub(m.base, s.base)?,
                 });
                 new_segments.push(Segment {
                     base: m.dest,
                     range: m.range,
                 });
                 new_segments.push(Segment {
-                    base: m.base + m.range,
-                    range: s.range - m.base + s.base - m.range,
+                    base: m_end,
+                    range: arith::sub(s_end, m_end)?,
                 });
                 found = true;
                 continue;
             }
 
-            if s.base < m.base && s.base + s.range > m.base && s.base + s.range < m.base + m.range {
+            if s.base < m.base && s_end > m.base && s_end < m_end {
                 // segment partially overlapping map
                 new_segments.push(Segment {
                     base: s.base,
-                    range: m.base - s.base,
+                    range: arith::sub(m.base, s.base)?,
                 });
                 new_segments.push(Segment {
                     base: m.dest,
-                    range: s.base + s.range - m.base,
+                    range: arith::sub(s_end, m.base)?,
                 });
                 found = true;
                 continue;
             }
 
-            if s.base > m.base && s.base < m.base + m.range && s.base + s.range > m.base + m.range {
+            if s.base > m.base && s.base < m_end && s_end > m_end {
                 // segment partially overlapping map
                 new_segments.push(Segment {
-                    base: m.dest + s.base - m.base,
-                    range: m.base + m.range - s.base,
+                    base: shift(s.base)?,
+                    range: arith::sub(m_end, s.base)?,
                 });
                 new_segments.push(Segment {
-                    base: m.base + m.range,
-                    range: s.base + s.range - m.base - m.range,
+                    base: m_end,
+                    range: arith::sub(s_end, m_end)?,
                 });
                 found = true;
                 continue;
@@ -153,19 +193,17 @@ fn process_map(segments: Vec<Segment>, maps_list: &[Vec<MyMap>]) -> Result<i64>
     process_map(new_segments, &maps_list[1..])
 }
 
-fn process2(input: &Vec<String>) -> Result<i64> {
-    let mut seeds = Vec::new();
+fn process2(input: &[String]) -> Result<Answer> {
+    let (i, seeds) = read_seeds(input)?;
+    if seeds.len() % 2 != 0 {
+        return Err(
+            ParseError::line(DAY, i, &input[i], "seeds must come as (start, range) pairs").into(),
+        );
+    }
     let mut all_maps = Vec::new();
     for (line_index, lines) in input.iter().enumerate() {
-        if lines.contains("seeds:") {
-            seeds = lines.split(':').collect::<Vec<&str>>()[1]
-                .split_whitespace()
-                .map(|x| x.parse::<i64>().unwrap())
-                .collect::<Vec<i64>>();
-        }
-
         if lines.contains("map:") {
-            all_maps.push(build_map(&input[line_index + 1..].to_vec())?);
+            all_maps.push(build_map(input, line_index + 1)?);
         }
     }
 
@@ -177,20 +215,175 @@ fn process2(input: &Vec<String>) -> Result<i64> {
         })
         .collect::<Vec<Segment>>();
 
-    process_map(segments, &all_maps)
+    Ok(Answer::new(process_map(segments, &all_maps)?)?)
+}
+
+fn show_seeds(input: &[String]) -> Result<String> {
+    Ok(format!("{:?}", read_seeds(input)?.1))
+}
+
+/// Source and destination ranges of each map of the almanac.
+fn show_maps(input: &[String]) -> Result<String> {
+    let mut lines = Vec::new();
+    for (i, header) in input
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.contains("map:"))
+    {
+        lines.push(header.to_string());
+        for map in build_map(input, i + 1)? {
+            lines.push(format!(
+                "  {}..{} -> {}..{}",
+                map.base,
+                arith::add(map.base, map.range)?,
+                map.dest,
+                arith::add(map.dest, map.range)?
+            ));
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Seeds and every map, as read by both parts.
+fn parse(input: &[String]) -> Result<()> {
+    let (_, seeds) = read_seeds(input)?;
+    let mut all_maps = Vec::new();
+    for (line_index, lines) in input.iter().enumerate() {
+        if lines.contains("map:") {
+            all_maps.push(build_map(input, line_index + 1)?);
+        }
+    }
+    drop((seeds, all_maps));
+    Ok(())
+}
+
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    let mut violations = Vec::new();
+    match input.first() {
+        Some(line) if line.starts_with("seeds:") => match parse_seeds(0, line) {
+            Ok(seeds) if seeds.len() % 2 != 0 => violations.push(ParseError::line(
+                DAY,
+                0,
+                line,
+                "seeds must come as (start, range) pairs",
+            )),
+            Ok(_) => {}
+            Err(e) => violations.push(e),
+        },
+        line => violations.push(ParseError::line(
+            DAY,
+            0,
+            line.map_or("", |l| l.as_str()),
+            "expected `seeds:` on the first line",
+        )),
+    }
+
+    for (i, line) in input.iter().enumerate().skip(1) {
+        if line.is_empty() {
+            continue;
+        }
+        if line.ends_with("map:") {
+            if input.get(i + 1).is_none_or(|l| l.is_empty()) {
+                violations.push(ParseError::line(DAY, i, line, "map has no ranges"));
+            }
+            continue;
+        }
+
+        let v = line.split_whitespace().collect::<Vec<&str>>();
+        if v.len() != 3 {
+            violations.push(ParseError::line(
+                DAY,
+                i,
+                line,
+                "expected `<dest> <source> <range>`",
+            ));
+        }
+        violations.extend(
+            v.iter()
+                .filter_map(|x| parse_token::<i64>(DAY, i, line, x).err()),
+        );
+    }
+    violations
+}
+
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    const CATEGORIES: [&str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+    let limit = 100 * size as i64 + 100;
+
+    let seeds = (0..size.max(1))
+        .map(|_| format!("{} {}", rng.gen_range(0..limit), rng.gen_range(1..100)))
+        .collect::<Vec<String>>();
+    let mut lines = vec![format!("seeds: {}", seeds.join(" "))];
+
+    for categories in CATEGORIES.windows(2) {
+        lines.push("".into());
+        lines.push(format!("{}-to-{} map:", categories[0], categories[1]));
+
+        // split sources into contiguous ranges, and move some of them
+        let mut cuts = (0..size)
+            .map(|_| rng.gen_range(0..limit))
+            .collect::<Vec<i64>>();
+        cuts.extend([0, limit]);
+        cuts.sort();
+        cuts.dedup();
+        let mut ranges = Vec::new();
+        for c in cuts.windows(2) {
+            if rng.gen_bool(0.7) {
+                ranges.push(format!(
+                    "{} {} {}",
+                    rng.gen_range(0..limit),
+                    c[0],
+                    c[1] - c[0]
+                ));
+            }
+        }
+        match ranges.is_empty() {
+            true => lines.push(format!("{} 0 {}", rng.gen_range(0..limit), cuts[1])),
+            false => lines.extend(ranges),
+        }
+    }
+    lines
 }
 
-pub fn run() {
-    let res = process(&lines_from_file("./input/day05.txt"));
-    println!(" * {:?}", res.unwrap());
-    let res = process2(&lines_from_file("./input/day05.txt"));
-    println!(" * {:?}", res.unwrap());
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse,
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", process)],
+            vec![Implementation::new("default", process2)],
+        ],
+        inspectors: vec![
+            Inspector {
+                name: "seeds",
+                show: show_seeds,
+            },
+            Inspector {
+                name: "maps",
+                show: show_maps,
+            },
+        ],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::arith::Overflow;
     use crate::day05::process;
     use crate::day05::process2;
+    use crate::day05::show_maps;
 
     #[test]
     fn test1() {
@@ -271,4 +464,44 @@ mod tests {
         ];
         assert!(process2(&input).unwrap() == 46);
     }
+
+    #[test]
+    fn test3() {
+        let input = vec![
+            "seeds: 9223372036854775800 10".to_string(),
+            "".into(),
+            "seed-to-soil map:".into(),
+            "0 9223372036854775790 20".into(),
+        ];
+        assert!(process(&input).unwrap_err().downcast::<Overflow>().is_ok());
+        assert!(process2(&input).unwrap_err().downcast::<Overflow>().is_ok());
+        assert!(show_maps(&input)
+            .unwrap_err()
+            .downcast::<Overflow>()
+            .is_ok());
+    }
+
+    #[test]
+    fn test4() {
+        let input = vec![
+            "seeds: 79 14 55".to_string(),
+            "".into(),
+            "seed-to-soil map:".into(),
+            "50 98 2".into(),
+        ];
+        assert!(process(&input).is_ok());
+        let error = process2(&input).unwrap_err().to_string();
+        assert!(error.starts_with("error: seeds must come as (start, range) pairs\n --> day05:1:1"));
+
+        let input = vec!["seed-to-soil map:".to_string(), "50 98 2".into()];
+        assert!(process(&input)
+            .unwrap_err()
+            .to_string()
+            .starts_with("error: missing `seeds:` line"));
+        let input = vec!["seeds:".to_string()];
+        assert!(process2(&input)
+            .unwrap_err()
+            .to_string()
+            .starts_with("error: no seeds listed\n --> day05:1:1"));
+    }
 }
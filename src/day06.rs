@@ -1,75 +1,200 @@
 use anyhow::Result;
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    arith::{self, Answer},
+    error::{parse_token, ParseError},
+    registry::{Implementation, Solution},
+};
+
+const DAY: u32 = 6;
 
 fn beat_record(time: i64, distance: i64) -> Result<i64> {
-    Ok((1..time)
-        .map(|x| x * (time - x))
-        .map(|x| (x > distance) as i64)
-        .sum::<i64>())
+    let mut count = 0;
+    for x in 1..time {
+        if arith::mul(x, time - x)? > distance {
+            count += 1;
+        }
+    }
+    Ok(count)
 }
 
-fn process(input: &Vec<String>) -> Result<i64> {
-    let mut times = Vec::new();
-    let mut distances = Vec::new();
+/// Count winning hold times by solving x * (time - x) > distance for x.
+fn beat_record_closed_form(time: i64, distance: i64) -> Result<i64> {
+    let delta = arith::sub(arith::mul(time, time)?, arith::mul(4, distance)?)?;
+    if delta <= 0 {
+        return Ok(0);
+    }
 
-    for lines in input {
-        if lines.contains("Time:") {
-            times = lines.split(':').collect::<Vec<&str>>()[1]
-                .split_whitespace()
-                .map(|x| x.parse::<i64>().unwrap())
-                .collect::<Vec<i64>>();
-        }
+    // first winning hold time, fixed up for floating point rounding
+    let mut low = ((time as f64 - (delta as f64).sqrt()) / 2.0).floor() as i64 + 1;
+    while low > 1 && arith::mul(low - 1, time - low + 1)? > distance {
+        low -= 1;
+    }
+    while low < time && arith::mul(low, time - low)? <= distance {
+        low += 1;
+    }
 
-        if lines.contains("Distance:") {
-            distances = lines.split(':').collect::<Vec<&str>>()[1]
-                .split_whitespace()
-                .map(|x| x.parse::<i64>().unwrap())
-                .collect::<Vec<i64>>();
-        }
+    // winning hold times are symmetric around time / 2
+    Ok((time - 2 * low + 1).max(0))
+}
+
+fn parse_values(index: usize, line: &str) -> Result<Vec<i64>, ParseError> {
+    line.split(':').collect::<Vec<&str>>()[1]
+        .split_whitespace()
+        .map(|x| parse_token(DAY, index, line, x))
+        .collect()
+}
+
+fn parse_kerned_value(index: usize, line: &str) -> Result<i64, ParseError> {
+    let value = line.split(':').collect::<Vec<&str>>()[1].trim();
+    value
+        .replace(' ', "")
+        .parse::<i64>()
+        .map_err(|_| ParseError::at(DAY, index, line, value, "invalid number"))
+}
+
+/// Index of the line starting with `header`, and the line itself.
+fn find_header<'a>(input: &'a [String], header: &str) -> Result<(usize, &'a str), ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .find(|(_, line)| line.starts_with(header))
+        .map(|(i, line)| (i, line.as_str()))
+        .ok_or_else(|| ParseError::line(DAY, 0, "", format!("missing `{}` line", header)))
+}
+
+/// Times and distances of the races, as many of each.
+fn parse_races(input: &[String]) -> Result<(Vec<i64>, Vec<i64>), ParseError> {
+    let (i, line) = find_header(input, "Time:")?;
+    let times = parse_values(i, line)?;
+    let (i, line) = find_header(input, "Distance:")?;
+    let distances = parse_values(i, line)?;
+    if times.len() != distances.len() {
+        let message = format!(
+            "got {} distances for {} times",
+            distances.len(),
+            times.len()
+        );
+        return Err(ParseError::line(DAY, i, line, message));
     }
+    Ok((times, distances))
+}
+
+/// Time and distance of the single race read by kerning.
+fn parse_kerned_race(input: &[String]) -> Result<(i64, i64), ParseError> {
+    let (i, line) = find_header(input, "Time:")?;
+    let time = parse_kerned_value(i, line)?;
+    let (i, line) = find_header(input, "Distance:")?;
+    Ok((time, parse_kerned_value(i, line)?))
+}
 
-    let mut mul = 1;
+fn process(input: &[String], count: fn(i64, i64) -> Result<i64>) -> Result<Answer> {
+    let (times, distances) = parse_races(input)?;
+
+    let mut mul = Answer::one();
     for (i, time) in times.iter().enumerate() {
-        mul *= beat_record(*time, distances[i])?;
+        mul.mul(count(*time, distances[i])?)?;
     }
 
     Ok(mul)
 }
 
-fn process2(input: &Vec<String>) -> Result<i64> {
-    let mut time = 0;
-    let mut distance = 0;
-    for lines in input {
-        if lines.contains("Time:") {
-            time = lines.split(':').collect::<Vec<&str>>()[1]
-                .replace(' ', "")
-                .parse::<i64>()
-                .unwrap();
+fn process2(input: &[String], count: fn(i64, i64) -> Result<i64>) -> Result<Answer> {
+    let (time, distance) = parse_kerned_race(input)?;
+    Ok(Answer::new(count(time, distance)?)?)
+}
+
+/// Races of part 1 and the kerned race of part 2.
+fn parse(input: &[String]) -> Result<()> {
+    parse_races(input)?;
+    parse_kerned_race(input)?;
+    Ok(())
+}
+
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    let mut violations = Vec::new();
+    let mut lengths = Vec::new();
+    for header in ["Time:", "Distance:"] {
+        match find_header(input, header).and_then(|(i, line)| Ok((i, parse_values(i, line)?))) {
+            Ok((i, values)) => lengths.push((i, values.len())),
+            Err(e) => violations.push(e),
         }
-        if lines.contains("Distance:") {
-            distance = lines.split(':').collect::<Vec<&str>>()[1]
-                .replace(' ', "")
-                .parse::<i64>()
-                .unwrap();
+    }
+
+    if let [(_, times), (i, distances)] = lengths[..] {
+        if times != distances {
+            violations.push(ParseError::line(
+                DAY,
+                i,
+                &input[i],
+                format!("got {} distances for {} times", distances, times),
+            ));
         }
     }
-    beat_record(time, distance)
+    violations
 }
 
-pub fn run() {
-    let input = vec![
-        "Time:        54     94     65     92".to_string(),
-        "Distance:   302   1476   1029   1404".into(),
-    ];
-    let res = process(&input);
-    println!(" * {:?}", res.unwrap());
-    let res = process2(&input);
-    println!(" * {:?}", res.unwrap());
+/// Generate `size` races, at most eight. Part 2 kerns all of them into a single
+/// race, so the races share eight digits of time, keeping it below 10^8 like in
+/// puzzle inputs.
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    let count = size.clamp(1, 8);
+    let digits = 8 / count as u32;
+    let races = (0..count)
+        .map(|_| {
+            let time = rng.gen_range(10i64.pow(digits - 1).max(2)..10i64.pow(digits));
+            (time, rng.gen_range(0..time * time / 4))
+        })
+        .collect::<Vec<(i64, i64)>>();
+
+    // distances have up to twice as many digits as times
+    let width = 2 * digits as usize;
+    vec![
+        races.iter().fold("Time:    ".to_string(), |acc, (t, _)| {
+            format!("{} {:>w$}", acc, t, w = width)
+        }),
+        races.iter().fold("Distance:".to_string(), |acc, (_, d)| {
+            format!("{} {:>w$}", acc, d, w = width)
+        }),
+    ]
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse,
+        generate,
+        parts: vec![
+            vec![
+                Implementation::new("brute-force", |input| process(input, beat_record)),
+                Implementation::new("closed-form", |input| {
+                    process(input, beat_record_closed_form)
+                }),
+            ],
+            vec![
+                Implementation::new("brute-force", |input| process2(input, beat_record)),
+                Implementation::new("closed-form", |input| {
+                    process2(input, beat_record_closed_form)
+                }),
+            ],
+        ],
+        inspectors: vec![],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::day06::beat_record;
+    use crate::day06::beat_record_closed_form;
+    use crate::day06::generate;
     use crate::day06::process;
     use crate::day06::process2;
+    use crate::error::ParseError;
 
     #[test]
     fn test1() {
@@ -77,7 +202,8 @@ mod tests {
             "Time:      7  15   30".to_string(),
             "Distance:  9  40  200".into(),
         ];
-        assert!(process(&input).unwrap() == 288);
+        assert!(process(&input, beat_record).unwrap() == 288);
+        assert!(process(&input, beat_record_closed_form).unwrap() == 288);
     }
 
     #[test]
@@ -86,6 +212,42 @@ mod tests {
             "Time:      7  15   30".to_string(),
             "Distance:  9  40  200".into(),
         ];
-        assert!(process2(&input).unwrap() == 71503);
+        assert!(process2(&input, beat_record).unwrap() == 71503);
+        assert!(process2(&input, beat_record_closed_form).unwrap() == 71503);
+    }
+
+    #[test]
+    fn test3() {
+        for (seed, size) in [3, 1000].into_iter().enumerate() {
+            let input = generate(&mut StdRng::seed_from_u64(seed as u64), size);
+            assert!(
+                process(&input, beat_record).unwrap()
+                    == process(&input, beat_record_closed_form).unwrap()
+            );
+            assert!(
+                process2(&input, beat_record).unwrap()
+                    == process2(&input, beat_record_closed_form).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test4() {
+        let input = vec!["Time:      7  15   30".to_string()];
+        let err = process2(&input, beat_record)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert!(err.message == "missing `Distance:` line");
+
+        let input = vec![
+            "Time:      7  15   30".to_string(),
+            "Distance:  9  40".into(),
+        ];
+        let err = process(&input, beat_record)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert!(err.line == 2 && err.message == "got 2 distances for 3 times");
     }
 }
@@ -1,17 +1,57 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
 
 use anyhow::{bail, Result};
+use rand::{rngs::StdRng, Rng};
 
-use crate::lines_from_file;
+use crate::{
+    arith::{self, Answer},
+    error::{parse_token, ParseError},
+    registry::{Implementation, Solution},
+};
+
+const DAY: u32 = 7;
+
+fn parse_hand(index: usize, line: &str) -> Result<(String, i32), ParseError> {
+    let [cards, bet] = line.split_whitespace().collect::<Vec<&str>>()[..] else {
+        return Err(ParseError::line(
+            DAY,
+            index,
+            line,
+            "expected `<hand> <bet>`",
+        ));
+    };
+    if cards.chars().count() != 5 {
+        return Err(ParseError::at(
+            DAY,
+            index,
+            line,
+            cards,
+            "a hand must have 5 cards",
+        ));
+    }
+    if let Some((i, c)) = cards
+        .char_indices()
+        .find(|(_, c)| !"AKQJT98765432".contains(*c))
+    {
+        let offset = line.len() - line.trim_start().len() + i;
+        return Err(ParseError::new(
+            DAY,
+            index,
+            line,
+            offset..offset + c.len_utf8(),
+            "unknown card",
+        ));
+    }
+    Ok((cards.to_string(), parse_token(DAY, index, line, bet)?))
+}
 
 fn find_hand_type(hand: &str) -> Result<i32> {
     let mut cards: HashMap<char, i32> = HashMap::new();
     for c in hand.chars() {
-        if cards.get(&c).is_some() {
-            cards.insert(c, cards.get(&c).unwrap() + 1);
-        } else {
-            cards.insert(c, 1);
-        }
+        *cards.entry(c).or_insert(0) += 1;
     }
 
     match cards.len() {
@@ -75,25 +115,18 @@ fn compare_hands(hand1: &str, hand2: &str) -> Result<Ordering> {
     Ok(Ordering::Equal)
 }
 
-fn process(input: Vec<String>) -> Result<i32> {
+fn process(input: &[String]) -> Result<Answer> {
     let mut hands: Vec<(String, i32)> = Vec::new();
-    for line in input {
-        let cards = line.split_whitespace().collect::<Vec<&str>>()[0].to_string();
-        let bet = line.split_whitespace().collect::<Vec<&str>>()[1]
-            .parse::<i32>()
-            .unwrap();
-        hands.push((cards, bet));
+    for (i, line) in input.iter().enumerate() {
+        hands.push(parse_hand(i, line)?);
     }
 
     hands.sort_by(|a, b| compare_hands(&a.0, &b.0).unwrap());
 
-    let mut total = 0;
-    let mut index = 1;
-
-    hands.iter().for_each(|(_, x)| {
-        total += x * index;
-        index += 1;
-    });
+    let mut total = Answer::zero();
+    for (index, (_, x)) in (1..).zip(hands.iter()) {
+        total.add(arith::mul(*x, index)?)?;
+    }
 
     Ok(total)
 }
@@ -106,11 +139,7 @@ fn improve_hand(hand: &String) -> Result<String> {
 
     let mut map: HashMap<char, i32> = HashMap::new();
     for c in hand.chars() {
-        if map.get(&c).is_some() {
-            map.insert(c, map.get(&c).unwrap() + 1);
-        } else {
-            map.insert(c, 1);
-        }
+        *map.entry(c).or_insert(0) += 1;
     }
 
     if !map.contains_key(&'J') {
@@ -167,47 +196,76 @@ fn compare_hands_with_joker(hand1: &String, hand2: &String) -> Result<Ordering>
     Ok(Ordering::Equal)
 }
 
-fn process2(input: Vec<String>) -> Result<i32> {
+fn process2(input: &[String]) -> Result<Answer> {
     let mut hands: Vec<(String, i32)> = Vec::new();
-    for line in input {
-        let cards = line.split_whitespace().collect::<Vec<&str>>()[0].to_string();
-        let bet = line.split_whitespace().collect::<Vec<&str>>()[1]
-            .parse::<i32>()
-            .unwrap();
-        hands.push((cards, bet));
+    for (i, line) in input.iter().enumerate() {
+        hands.push(parse_hand(i, line)?);
     }
 
     hands.sort_by(|a, b| compare_hands_with_joker(&a.0, &b.0).unwrap());
 
-    let mut total = 0;
-    let mut index = 1;
-
-    hands.iter().for_each(|(_, x)| {
-        total += x * index;
-        index += 1;
-    });
+    let mut total = Answer::zero();
+    for (index, (_, x)) in (1..).zip(hands.iter()) {
+        total.add(arith::mul(*x, index)?)?;
+    }
 
     Ok(total)
 }
 
-pub fn run() {
-    let _input = vec![
-        "32T3K 765".to_string(),
-        "T55J5 684".into(),
-        "KK677 28".into(),
-        "KTJJT 220".into(),
-        "QQQJA 483".into(),
-    ];
-    let res = process(lines_from_file("./input/day07.txt"));
-    println!(" * {:?}", res.unwrap());
-    let res = process2(lines_from_file("./input/day07.txt"));
-    println!(" * {:?}", res.unwrap());
+fn parse(input: &[String]) -> Result<()> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, line)| parse_hand(i, line))
+        .collect::<Result<Vec<(String, i32)>, ParseError>>()?;
+    Ok(())
+}
+
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| parse_hand(i, line).err())
+        .collect()
+}
+
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    const CARDS: &[u8] = b"AKQJT98765432";
+
+    // hands are all different in puzzle inputs
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+    while lines.len() < size.min(CARDS.len().pow(5)) {
+        let hand = (0..5)
+            .map(|_| CARDS[rng.gen_range(0..CARDS.len())] as char)
+            .collect::<String>();
+        if seen.insert(hand.clone()) {
+            lines.push(format!("{} {}", hand, rng.gen_range(1..1000)));
+        }
+    }
+    lines
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse,
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", process)],
+            vec![Implementation::new("default", process2)],
+        ],
+        inspectors: vec![],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day07::process;
     use crate::day07::process2;
+    use crate::day07::validate;
 
     #[test]
     fn test1() {
@@ -218,7 +276,7 @@ mod tests {
             "KTJJT 220".into(),
             "QQQJA 483".into(),
         ];
-        assert!(process(input).unwrap() == 6440);
+        assert!(process(&input).unwrap() == 6440);
     }
 
     #[test]
@@ -230,6 +288,20 @@ mod tests {
             "KTJJT 220".into(),
             "QQQJA 483".into(),
         ];
-        assert!(process2(input).unwrap() == 5905);
+        assert!(process2(&input).unwrap() == 5905);
+    }
+
+    #[test]
+    fn test3() {
+        let input = vec!["éKQJ 12".to_string(), " KéQJT 12".into()];
+        let violations = validate(&input);
+        assert!(violations.len() == 2);
+        assert!(violations[0]
+            .to_string()
+            .starts_with("error: a hand must have 5 cards"));
+        assert!(violations[1]
+            .to_string()
+            .starts_with("error: unknown card\n --> day07:2:3"));
+        assert!(process(&input).is_err() && process2(&input).is_err());
     }
 }
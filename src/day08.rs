@@ -1,34 +1,102 @@
-use num::integer::lcm;
-use std::collections::HashMap;
+use num::integer::gcd;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{bail, Result};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
 
-use crate::lines_from_file;
+use crate::{
+    arith::{self, Answer},
+    error::ParseError,
+    registry::{Implementation, Inspector, Solution},
+};
 
-fn process(input: Vec<String>) -> Result<i32> {
-    let commands = input[0].chars().collect::<Vec<char>>();
-    let map = input[2..]
+const DAY: u32 = 8;
+
+type Network<'a> = HashMap<&'a str, Vec<&'a str>>;
+
+fn parse_node(index: usize, line: &str) -> Result<(&str, Vec<&str>), ParseError> {
+    let Some((key, value)) = line.split_once(" = ") else {
+        return Err(ParseError::line(
+            DAY,
+            index,
+            line,
+            "expected `<node> = (<left>, <right>)`",
+        ));
+    };
+    let Some(value) = value.strip_prefix('(').and_then(|x| x.strip_suffix(')')) else {
+        return Err(ParseError::at(
+            DAY,
+            index,
+            line,
+            value,
+            "expected `(<left>, <right>)`",
+        ));
+    };
+    let value = value.split(", ").collect::<Vec<&str>>();
+    if value.len() != 2 {
+        return Err(ParseError::at(
+            DAY,
+            index,
+            line,
+            value[0],
+            "expected exactly two nodes",
+        ));
+    }
+    Ok((key, value))
+}
+
+fn parse_network(input: &[String]) -> Result<(Vec<char>, Network<'_>), ParseError> {
+    let Some(first) = input.first().filter(|first| !first.is_empty()) else {
+        return Err(ParseError::line(DAY, 0, "", "missing instructions"));
+    };
+    if let Some((i, c)) = first.char_indices().find(|(_, c)| *c != 'L' && *c != 'R') {
+        return Err(ParseError::new(
+            DAY,
+            0,
+            first,
+            i..i + c.len_utf8(),
+            "unknown command, expected `L` or `R`",
+        ));
+    }
+    let commands = first.chars().collect::<Vec<char>>();
+
+    let nodes = input
+        .iter()
+        .enumerate()
+        .skip(2)
+        .map(|(i, x)| Ok((i, parse_node(i, x)?)))
+        .collect::<Result<Vec<(usize, (&str, Vec<&str>))>, ParseError>>()?;
+    let map = nodes
         .iter()
-        .map(|x| {
-            let mut parts = x.split(" = ");
-            let key = parts.next().unwrap();
-            let value = parts
-                .next()
-                .unwrap()
-                .trim_start_matches('(')
-                .trim_end_matches(')')
-                .split(", ")
-                .collect::<Vec<&str>>();
-            (key, value)
-        })
-        .collect::<HashMap<&str, Vec<&str>>>();
+        .map(|(_, (key, value))| (*key, value.clone()))
+        .collect::<Network>();
+
+    for (i, (_, value)) in &nodes {
+        if let Some(node) = value.iter().find(|node| !map.contains_key(*node)) {
+            return Err(ParseError::at(DAY, *i, &input[*i], node, "unknown node"));
+        }
+    }
+    Ok((commands, map))
+}
 
+/// Follow the commands from `key` until reaching a node accepted by `end`,
+/// returning this node and the number of steps.
+fn walk<'a>(
+    commands: &[char],
+    map: &Network<'a>,
+    mut key: &'a str,
+    end: fn(&str) -> bool,
+) -> Result<(&'a str, usize)> {
+    let start = key;
     let mut index = 0;
-    let mut key = "AAA";
     loop {
-        if key == "ZZZ" {
+        if end(key) {
             break;
         }
+        // past this many steps, a (node, command) state has repeated
+        if index > map.len() * commands.len() {
+            bail!("`{}` never reaches an end node", start);
+        }
 
         key = match commands[index % commands.len()] {
             'L' => map[key][0],
@@ -38,73 +106,210 @@ fn process(input: Vec<String>) -> Result<i32> {
 
         index += 1;
     }
-
-    Ok(index as i32)
+    Ok((key, index))
 }
 
-fn process2(input: Vec<String>) -> Result<i64> {
-    let commands = input[0].chars().collect::<Vec<char>>();
-    let map = input[2..]
-        .iter()
-        .map(|x| {
-            let mut parts = x.split(" = ");
-            let key = parts.next().unwrap();
-            let value = parts
-                .next()
-                .unwrap()
-                .trim_start_matches('(')
-                .trim_end_matches(')')
-                .split(", ")
-                .collect::<Vec<&str>>();
-            (key, value)
-        })
-        .collect::<HashMap<&str, Vec<&str>>>();
-
-    let keys = map
-        .keys()
-        .filter(|x| x.ends_with('A')).copied();
+fn process(input: &[String]) -> Result<Answer> {
+    let (commands, map) = parse_network(input)?;
+    if let Some(node) = ["AAA", "ZZZ"]
+        .into_iter()
+        .find(|node| !map.contains_key(node))
+    {
+        return Err(ParseError::line(DAY, 0, &input[0], format!("missing node `{}`", node)).into());
+    }
+    let (_, index) = walk(&commands, &map, "AAA", |key| key == "ZZZ")?;
+    Ok(Answer::new(index as i64)?)
+}
 
-    let mut res: Option<i64> = None;
+fn process2(input: &[String]) -> Result<Answer> {
+    let (commands, map) = parse_network(input)?;
 
-    for mut key in keys {
-        let mut index = 0;
-        loop {
-            if key.ends_with('Z') {
-                break;
-            }
+    let keys = map.keys().filter(|x| x.ends_with('A')).copied();
 
-            key = match commands[index % commands.len()] {
-                'L' => map[key][0],
-                'R' => map[key][1],
-                _ => bail!("Unknown command"),
-            };
+    let mut res: Option<i64> = None;
 
-            index += 1;
-        }
+    for key in keys {
+        let (_, index) = walk(&commands, &map, key, |key| key.ends_with('Z'))?;
 
         match res {
             None => res = Some(index as i64),
-            Some(x) => res = Some(lcm(x, index as i64)),
+            Some(x) => res = Some(arith::mul(x / gcd(x, index as i64), index as i64)?),
         }
     }
 
     match res {
         None => bail!("No result"),
-        Some(x) => Ok(x),
+        Some(x) => Ok(Answer::new(x)?),
+    }
+}
+
+fn show_network(input: &[String]) -> Result<String> {
+    let (commands, map) = parse_network(input)?;
+    let mut nodes = map.keys().copied().collect::<Vec<&str>>();
+    nodes.sort();
+    let starts = nodes
+        .iter()
+        .filter(|x| x.ends_with('A'))
+        .copied()
+        .collect::<Vec<&str>>();
+    let ends = nodes
+        .iter()
+        .filter(|x| x.ends_with('Z'))
+        .copied()
+        .collect::<Vec<&str>>();
+    Ok(format!(
+        "{} commands, {} nodes\nstart nodes: {}\nend nodes: {}",
+        commands.len(),
+        nodes.len(),
+        starts.join(", "),
+        ends.join(", ")
+    ))
+}
+
+/// Steps taken by each ghost to reach its first end node, combined with a LCM in part 2.
+fn show_ghosts(input: &[String]) -> Result<String> {
+    let (commands, map) = parse_network(input)?;
+    let mut starts = map
+        .keys()
+        .filter(|x| x.ends_with('A'))
+        .copied()
+        .collect::<Vec<&str>>();
+    starts.sort();
+
+    let mut lines = Vec::new();
+    for start in starts {
+        let (end, steps) = walk(&commands, &map, start, |key| key.ends_with('Z'))?;
+        lines.push(format!("{} -> {} in {} steps", start, end, steps));
+    }
+    Ok(lines.join("\n"))
+}
+
+/// Syntax errors and dangling nodes, and for part 1 its missing start or end node.
+pub fn validate(input: &[String], part: usize) -> Vec<ParseError> {
+    let mut violations = Vec::new();
+    let first = input.first().map_or("", |l| l.as_str());
+    for (i, c) in first.char_indices().filter(|(_, c)| *c != 'L' && *c != 'R') {
+        violations.push(ParseError::new(
+            DAY,
+            0,
+            first,
+            i..i + c.len_utf8(),
+            "unknown command",
+        ));
+    }
+    if first.is_empty() {
+        violations.push(ParseError::line(DAY, 0, first, "missing instructions"));
+    }
+    if let Some(line) = input.get(1).filter(|l| !l.is_empty()) {
+        violations.push(ParseError::line(DAY, 1, line, "expected an empty line"));
+    }
+
+    let mut nodes: HashMap<&str, usize> = HashMap::new();
+    let mut edges = Vec::new();
+    for (i, line) in input.iter().enumerate().skip(2) {
+        match parse_node(i, line) {
+            Ok((key, value)) => {
+                if nodes.insert(key, i).is_some() {
+                    violations.push(ParseError::at(DAY, i, line, key, "duplicate node"));
+                }
+                edges.extend(value.into_iter().map(|v| (i, v)));
+            }
+            Err(e) => violations.push(e),
+        }
+    }
+
+    for (i, node) in edges {
+        if !nodes.contains_key(node) {
+            violations.push(ParseError::at(DAY, i, &input[i], node, "unknown node"));
+        }
     }
+    for node in ["AAA", "ZZZ"].into_iter().filter(|_| part == 0) {
+        if !nodes.contains_key(node) {
+            violations.push(ParseError::line(
+                DAY,
+                0,
+                first,
+                format!("missing node `{}`", node),
+            ));
+        }
+    }
+    violations
+}
+
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    // only start and end nodes may end with `A` or `Z`
+    let mut seen = HashSet::from(["AAA".to_string(), "ZZZ".to_string()]);
+    let mut name = |rng: &mut StdRng, last: Option<char>| loop {
+        let mut name = (0..2).map(|_| rng.gen_range('A'..='Z')).collect::<String>();
+        name.push(last.unwrap_or_else(|| rng.gen_range('B'..='Y')));
+        if seen.insert(name.clone()) {
+            return name;
+        }
+    };
+
+    // each ghost walks a cycle whose length is also the distance to its end node,
+    // so that the LCM of these lengths is the answer of part 2
+    let mut nodes = Vec::new();
+    for ghost in 0..rng.gen_range(1..=4) {
+        let (start, end) = match ghost {
+            0 => ("AAA".to_string(), "ZZZ".to_string()),
+            _ => (name(rng, Some('A')), name(rng, Some('Z'))),
+        };
+        let path = (0..rng.gen_range(1..=size.max(1)))
+            .map(|_| name(rng, None))
+            .collect::<Vec<String>>();
+
+        nodes.push((start, path[0].clone()));
+        for step in path.windows(2) {
+            nodes.push((step[0].clone(), step[1].clone()));
+        }
+        nodes.push((path[path.len() - 1].clone(), end.clone()));
+        nodes.push((end, path[0].clone()));
+    }
+    nodes.shuffle(rng);
+
+    let commands = (0..rng.gen_range(2..10))
+        .map(|_| if rng.gen_bool(0.5) { 'L' } else { 'R' })
+        .collect::<String>();
+    let mut lines = vec![commands, "".into()];
+    lines.extend(
+        nodes
+            .iter()
+            .map(|(key, next)| format!("{} = ({}, {})", key, next, next)),
+    );
+    lines
 }
 
-pub fn run() {
-    let res = process(lines_from_file("./input/day08.txt"));
-    println!(" * {:?}", res.unwrap());
-    let res = process2(lines_from_file("./input/day08.txt"));
-    println!(" * {:?}", res.unwrap());
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate,
+        parse: |input| Ok(parse_network(input).map(|_| ())?),
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", process)],
+            vec![Implementation::new("default", process2)],
+        ],
+        inspectors: vec![
+            Inspector {
+                name: "network",
+                show: show_network,
+            },
+            Inspector {
+                name: "ghosts",
+                show: show_ghosts,
+            },
+        ],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day08::process;
     use crate::day08::process2;
+    use crate::day08::validate;
+    use crate::error::ParseError;
 
     #[test]
     fn test1() {
@@ -115,7 +320,7 @@ mod tests {
             "BBB = (AAA, ZZZ)".into(),
             "ZZZ = (ZZZ, ZZZ)".into(),
         ];
-        assert!(process(input).unwrap() == 6);
+        assert!(process(&input).unwrap() == 6);
     }
 
     #[test]
@@ -132,6 +337,84 @@ mod tests {
             "22Z = (22B, 22B)".into(),
             "XXX = (XXX, XXX)".into(),
         ];
-        assert!(process2(input).unwrap() == 6);
+        assert!(process2(&input).unwrap() == 6);
+
+        // only part 1 walks from `AAA` to `ZZZ`
+        assert!(validate(&input, 1).is_empty());
+        let violations = validate(&input, 0);
+        assert!(violations.len() == 2 && violations[0].message == "missing node `AAA`");
+    }
+
+    #[test]
+    fn test3() {
+        let input = vec![
+            "LLX".to_string(),
+            "".into(),
+            "AAA = (ZZZ, ZZZ)".into(),
+            "ZZZ = (ZZZ, ZZZ)".into(),
+        ];
+        let err = process(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert!(err.line == 1 && err.column == 3 && err.text == "X");
+    }
+
+    #[test]
+    fn test4() {
+        let input = vec![
+            "LR".to_string(),
+            "".into(),
+            "AAA = (BBB, ZZZ)".into(),
+            "ZZZ = (ZZZ, CCC)".into(),
+        ];
+        let violations = validate(&input, 0);
+        assert!(violations.len() == 2);
+        assert!(
+            violations[0].line == 3 && violations[0].column == 8 && violations[0].text == "BBB"
+        );
+        assert!(
+            violations[1].line == 4 && violations[1].column == 13 && violations[1].text == "CCC"
+        );
+    }
+
+    #[test]
+    fn test5() {
+        let input = vec!["LR".to_string(), "".into(), "AAA = (BBB, BBB)".into()];
+        let err = process(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert!(err.line == 3 && err.message == "unknown node" && err.text == "BBB");
+
+        let input = vec!["LR".to_string(), "".into(), "AAA = (AAA, AAA)".into()];
+        let err = process(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert!(err.message == "missing node `ZZZ`");
+
+        let input = vec![
+            "LR".to_string(),
+            "".into(),
+            "AAA = (BBB, BBB)".into(),
+            "BBB = (AAA, AAA)".into(),
+            "ZZZ = (ZZZ, ZZZ)".into(),
+        ];
+        assert!(process(&input).unwrap_err().to_string() == "`AAA` never reaches an end node");
+        assert!(process2(&input).is_err());
+
+        let input = vec![
+            "".to_string(),
+            "".into(),
+            "AAA = (ZZZ, ZZZ)".into(),
+            "ZZZ = (ZZZ, ZZZ)".into(),
+        ];
+        let err = process(&input)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert!(err.line == 1 && err.message == "missing instructions");
+        assert!(process2(&input).is_err());
     }
 }
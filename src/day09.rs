@@ -1,77 +1,136 @@
 use anyhow::Result;
+use rand::{rngs::StdRng, Rng};
 
-use crate::lines_from_file;
+use crate::{
+    arith::{self, Answer, Overflow},
+    error::{parse_token, ParseError},
+    parallel,
+    registry::{Implementation, Solution},
+};
+
+const DAY: u32 = 9;
 
 fn is_vec_all_zeros(vec: &[i32]) -> bool {
     vec.iter().all(|&x| x == 0)
 }
 
-fn process_line(line: String, next_values: &mut Vec<i32>) -> Result<i32> {
-    let mut vec = line
-        .split_whitespace()
-        .map(|x| x.parse::<i32>().unwrap())
-        .collect::<Vec<i32>>();
+fn parse_line(index: usize, line: &str) -> Result<Vec<i32>, ParseError> {
+    line.split_whitespace()
+        .map(|x| parse_token(DAY, index, line, x))
+        .collect()
+}
+
+fn process_line(index: usize, line: &str, next_values: &mut Vec<i32>) -> Result<i32> {
+    let mut vec = parse_line(index, line)?;
 
     while !is_vec_all_zeros(&vec) {
         next_values.push(vec[vec.len() - 1]);
         vec = vec
             .iter()
             .zip(vec.iter().skip(1))
-            .map(|(a, b)| b - a)
-            .collect::<Vec<i32>>();
+            .map(|(a, b)| arith::sub(*b, *a))
+            .collect::<Result<Vec<i32>, Overflow>>()?;
     }
 
-    Ok(next_values.iter().fold(0, |acc, x| acc + *x))
+    Ok(next_values
+        .iter()
+        .try_fold(0, |acc, x| arith::add(acc, *x))?)
 }
 
-fn process(input: Vec<String>) -> Result<i32> {
-    let mut res = 0;
-    let mut next_values: Vec<i32> = vec![];
-    for line in input {
-        next_values.clear();
-        res += process_line(line, &mut next_values).unwrap();
+fn process(input: &[String]) -> Result<Answer> {
+    let mut res = Answer::zero();
+    for x in parallel::map(input, |i, line| process_line(i, line, &mut vec![])) {
+        res.add(x?)?;
     }
     Ok(res)
 }
 
-fn process_line2(line: String, first_values: &mut Vec<i32>) -> Result<i32> {
-    let mut vec = line
-        .split_whitespace()
-        .map(|x| x.parse::<i32>().unwrap())
-        .collect::<Vec<i32>>();
+fn process_line2(index: usize, line: &str, first_values: &mut Vec<i32>) -> Result<i32> {
+    let mut vec = parse_line(index, line)?;
 
     while !is_vec_all_zeros(&vec) {
         first_values.push(vec[0]);
         vec = vec
             .iter()
             .zip(vec.iter().skip(1))
-            .map(|(a, b)| b - a)
-            .collect::<Vec<i32>>();
+            .map(|(a, b)| arith::sub(*b, *a))
+            .collect::<Result<Vec<i32>, Overflow>>()?;
     }
 
     let mut res = 0;
     for x in first_values.iter().rev() {
-        res = x - res;
+        res = arith::sub(*x, res)?;
     }
 
     Ok(res)
 }
 
-fn process2(input: Vec<String>) -> Result<i32> {
-    let mut res = 0;
-    let mut first_values: Vec<i32> = vec![];
-    for line in input {
-        first_values.clear();
-        res += process_line2(line, &mut first_values).unwrap();
+fn process2(input: &[String]) -> Result<Answer> {
+    let mut res = Answer::zero();
+    for x in parallel::map(input, |i, line| process_line2(i, line, &mut vec![])) {
+        res.add(x?)?;
     }
     Ok(res)
 }
 
-pub fn run() {
-    let res = process(lines_from_file("./input/day09.txt"));
-    println!(" * {:?}", res.unwrap());
-    let res = process2(lines_from_file("./input/day09.txt"));
-    println!(" * {:?}", res.unwrap());
+fn parse(input: &[String]) -> Result<()> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, line)| parse_line(i, line))
+        .collect::<Result<Vec<Vec<i32>>, ParseError>>()?;
+    Ok(())
+}
+
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    let mut violations = Vec::new();
+    for (i, line) in input.iter().enumerate() {
+        if line.split_whitespace().next().is_none() {
+            violations.push(ParseError::line(DAY, i, line, "empty sequence"));
+        }
+        violations.extend(
+            line.split_whitespace()
+                .filter_map(|x| parse_token::<i32>(DAY, i, line, x).err()),
+        );
+    }
+    violations
+}
+
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            // sequences are polynomials, long enough to reach all zeros
+            let degree = rng.gen_range(0..5);
+            let coeffs = (0..=degree)
+                .map(|_| rng.gen_range(-5..=5))
+                .collect::<Vec<i32>>();
+            (0..rng.gen_range(degree + 2..=21))
+                .map(|x| {
+                    coeffs
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, c| acc * x + c)
+                        .to_string()
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse,
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", process)],
+            vec![Implementation::new("default", process2)],
+        ],
+        inspectors: vec![],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
@@ -86,7 +145,7 @@ mod tests {
             "1 3 6 10 15 21".into(),
             "10 13 16 21 30 45".into(),
         ];
-        assert!(process(input).unwrap() == 114);
+        assert!(process(&input).unwrap() == 114);
     }
 
     #[test]
@@ -96,6 +155,6 @@ mod tests {
             "1 3 6 10 15 21".into(),
             "10 13 16 21 30 45".into(),
         ];
-        assert!(process2(input).unwrap() == 2);
+        assert!(process2(&input).unwrap() == 2);
     }
 }
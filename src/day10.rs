@@ -1,11 +1,24 @@
+use std::collections::HashSet;
+
 use anyhow::{bail, Result};
 use multimap::MultiMap;
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    arith::{self, Answer},
+    error::ParseError,
+    registry::{Implementation, Solution},
+    validate,
+};
 
-use crate::lines_from_file;
+const DAY: u32 = 10;
 
-fn build_graph(input: &Vec<String>, graph: &mut MultiMap<usize, usize>) -> Result<()> {
+fn build_graph(input: &[String], graph: &mut MultiMap<usize, usize>) -> Result<()> {
+    if let Some(error) = validate::rectangular(DAY, input).into_iter().next() {
+        return Err(error.into());
+    }
     let nline = input.len();
-    let ncol = input[0].len();
+    let ncol = input[0].chars().count();
 
     for x in 0..ncol {
         for y in 0..nline {
@@ -73,7 +86,17 @@ fn build_graph(input: &Vec<String>, graph: &mut MultiMap<usize, usize>) -> Resul
                         graph.insert(x + y * ncol, x + 1 + y * ncol);
                     }
                 }
-                _ => {}
+                c => {
+                    let i = input[y].char_indices().nth(x).unwrap().0;
+                    return Err(ParseError::new(
+                        DAY,
+                        y,
+                        &input[y],
+                        i..i + c.len_utf8(),
+                        "unknown tile",
+                    )
+                    .into());
+                }
             }
         }
     }
@@ -81,17 +104,14 @@ fn build_graph(input: &Vec<String>, graph: &mut MultiMap<usize, usize>) -> Resul
     Ok(())
 }
 
-fn find_starting_point(input: &Vec<String>) -> Result<usize> {
-    let ncol = input[0].len();
-    let mut x = 0;
-    let mut y = 0;
-    for (i, line) in input.iter().enumerate() {
-        if let Some(pos) = line.find('S') {
-            x = pos;
-            y = i;
+fn find_starting_point(input: &[String]) -> Result<usize, ParseError> {
+    let ncol = input.first().map_or(0, |l| l.len());
+    for (y, line) in input.iter().enumerate() {
+        if let Some(x) = line.find('S') {
+            return Ok(x + y * ncol);
         }
     }
-    Ok(x + y * ncol)
+    Err(ParseError::line(DAY, 0, "", "no starting point `S` found"))
 }
 
 fn bfs(graph: &MultiMap<usize, usize>, root: usize) -> Vec<usize> {
@@ -113,19 +133,74 @@ fn bfs(graph: &MultiMap<usize, usize>, root: usize) -> Vec<usize> {
     visited
 }
 
-fn process(input: Vec<String>) -> Result<i32> {
+/// Follow the pipes from the starting point until getting back to it.
+fn walk_loop(graph: &MultiMap<usize, usize>, root: usize) -> Result<Vec<usize>> {
+    // only keep neighbors of the starting point which connect back to it
+    let Some(mut node) = graph
+        .get_vec(&root)
+        .and_then(|n| {
+            n.iter()
+                .find(|n| graph.get_vec(n).is_some_and(|v| v.contains(&root)))
+        })
+        .copied()
+    else {
+        bail!("No pipe connected to the starting point");
+    };
+
+    let mut path = vec![root];
+    let mut prev = root;
+    while node != root {
+        path.push(node);
+        let Some(&next) = graph
+            .get_vec(&node)
+            .and_then(|n| n.iter().find(|&&n| n != prev))
+        else {
+            bail!("Loop is broken at tile {}", node);
+        };
+        prev = node;
+        node = next;
+    }
+    Ok(path)
+}
+
+fn process(input: &[String]) -> Result<Answer> {
     let mut graph: MultiMap<usize, usize> = MultiMap::new();
 
-    build_graph(&input, &mut graph)?;
+    build_graph(input, &mut graph)?;
+    let root = find_starting_point(input)?;
 
-    let Ok(root) = find_starting_point(&input) else {
-        bail!("No starting point found");
-    };
+    Ok(Answer::new(bfs(&graph, root).len() as i64 / 2)?)
+}
 
-    Ok(bfs(&graph, root).len() as i32 / 2)
+/// Shape of the starting point, given by the pipes around it which connect back to it.
+fn start_shape(graph: &MultiMap<usize, usize>, root: usize, ncol: usize) -> Result<char> {
+    let linked = |node: usize| {
+        graph.get_vec(&root).is_some_and(|n| n.contains(&node))
+            && graph.get_vec(&node).is_some_and(|n| n.contains(&root))
+    };
+    let north = root >= ncol && linked(root - ncol);
+    let south = linked(root + ncol);
+    let x = root % ncol;
+    let west = x > 0 && linked(root - 1);
+    let east = x < ncol - 1 && linked(root + 1);
+    Ok(match (north, south, west, east) {
+        (true, true, false, false) => '|',
+        (false, false, true, true) => '-',
+        (true, false, false, true) => 'L',
+        (true, false, true, false) => 'J',
+        (false, true, true, false) => '7',
+        (false, true, false, true) => 'F',
+        _ => bail!("Starting point is not connected to exactly two pipes"),
+    })
 }
 
-fn ray_casting(input: &Vec<String>, x: usize, y: usize, visited: &[usize]) -> Result<i32> {
+fn ray_casting(
+    input: &[String],
+    x: usize,
+    y: usize,
+    visited: &[usize],
+    start: char,
+) -> Result<i32> {
     let ncol = input[0].len();
     let mut count = 0;
     if x == 0 || x == ncol - 1 {
@@ -138,38 +213,40 @@ fn ray_casting(input: &Vec<String>, x: usize, y: usize, visited: &[usize]) -> Re
         if !visited.contains(&(i + y * ncol)) {
             continue;
         }
-        if input[y].chars().nth(i).unwrap() == '|' {
+        let tile = match input[y].chars().nth(i).unwrap() {
+            'S' => start,
+            tile => tile,
+        };
+        if tile == '|' {
             count += 1;
         }
-        if input[y].chars().nth(i).unwrap() == '7' {
+        if tile == '7' {
             count += 1;
         }
-        if input[y].chars().nth(i).unwrap() == 'F' {
+        if tile == 'F' {
             count += 1;
         }
     }
     Ok(count)
 }
 
-fn process2(input: Vec<String>) -> Result<i32> {
-    let nline = input.len();
-    let ncol = input[0].len();
+fn process2(input: &[String]) -> Result<Answer> {
     let mut graph: MultiMap<usize, usize> = MultiMap::new();
-    let mut sum = 0;
+    let mut sum = Answer::zero();
 
-    build_graph(&input, &mut graph)?;
-    let Ok(root) = find_starting_point(&input) else {
-        bail!("No starting point found");
-    };
+    build_graph(input, &mut graph)?;
+    let (nline, ncol) = (input.len(), input[0].len());
+    let root = find_starting_point(input)?;
     let visited = bfs(&graph, root);
+    let start = start_shape(&graph, root, ncol)?;
 
     for x in 0..ncol {
         for y in 0..nline {
             if visited.contains(&(x + y * ncol)) {
                 continue;
             }
-            if ray_casting(&input, x, y, &visited)? % 2 == 1 {
-                sum += 1;
+            if ray_casting(input, x, y, &visited, start)? % 2 == 1 {
+                sum.add(1)?;
             }
         }
     }
@@ -177,17 +254,169 @@ fn process2(input: Vec<String>) -> Result<i32> {
     Ok(sum)
 }
 
-pub fn run() {
-    let res = process(lines_from_file("./input/day10.txt"));
-    println!(" * {:?}", res.unwrap());
-    let res = process2(lines_from_file("./input/day10.txt"));
-    println!(" * {:?}", res.unwrap());
+fn process2_shoelace(input: &[String]) -> Result<Answer> {
+    let mut graph: MultiMap<usize, usize> = MultiMap::new();
+
+    build_graph(input, &mut graph)?;
+    let ncol = input[0].len();
+    let root = find_starting_point(input)?;
+    let path = walk_loop(&graph, root)?;
+
+    // shoelace formula gives twice the area enclosed by the loop
+    let mut area = 0;
+    for (a, b) in path.iter().zip(path.iter().cycle().skip(1)) {
+        let (x1, y1) = ((a % ncol) as i64, (a / ncol) as i64);
+        let (x2, y2) = ((b % ncol) as i64, (b / ncol) as i64);
+        area = arith::add(area, arith::sub(arith::mul(x1, y2)?, arith::mul(x2, y1)?)?)?;
+    }
+
+    // Pick's theorem: area = interior + boundary / 2 - 1
+    Ok(Answer::new(area.abs() / 2 - path.len() as i64 / 2 + 1)?)
+}
+
+/// Graph of the pipes and its starting point.
+fn parse(input: &[String]) -> Result<()> {
+    let mut graph: MultiMap<usize, usize> = MultiMap::new();
+    build_graph(input, &mut graph)?;
+    find_starting_point(input)?;
+    Ok(())
+}
+
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    let mut violations = validate::rectangular(DAY, input);
+    violations.extend(validate::tiles(DAY, input, |c| ".S|-LJ7F".contains(c)));
+
+    let starts = input
+        .iter()
+        .enumerate()
+        .flat_map(|(i, line)| line.match_indices('S').map(move |(x, _)| (i, line, x)))
+        .collect::<Vec<_>>();
+    if starts.is_empty() {
+        violations.push(ParseError::line(DAY, 0, "", "no starting point `S` found"));
+    }
+    for (i, line, x) in starts.into_iter().skip(1) {
+        violations.push(ParseError::new(
+            DAY,
+            i,
+            line,
+            x..x + 1,
+            "more than one starting point",
+        ));
+    }
+    violations
+}
+
+/// Generate a loop as the outline of a random tree on a third resolution grid.
+///
+/// Each tree cell becomes a 3x3 loop `F-7`/`|.|`/`L-J`, merged with the loops
+/// of its neighbors in the tree.
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    let m = (size / 3).max(1);
+    let (n, s, e, w) = ((-1, 0), (1, 0), (0, 1), (0, -1));
+
+    // random spanning tree grown from the center
+    let root = (m as i32 / 2, m as i32 / 2);
+    let mut tree = HashSet::from([root]);
+    let mut links = HashSet::new();
+    let mut frontier = vec![(root, n), (root, s), (root, e), (root, w)];
+    let target = rng.gen_range(1..=m * m);
+    while tree.len() < target && !frontier.is_empty() {
+        let ((y, x), (dy, dx)) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+        let next = (y + dy, x + dx);
+        if next.0 < 0
+            || next.1 < 0
+            || next.0 >= m as i32
+            || next.1 >= m as i32
+            || !tree.insert(next)
+        {
+            continue;
+        }
+        links.insert(((y, x), next));
+        links.insert((next, (y, x)));
+        frontier.extend([n, s, e, w].map(|d| (next, d)));
+    }
+
+    let mut grid = vec![vec!['.'; size.max(3)]; size.max(3)];
+    for &(y, x) in &tree {
+        let linked = |(dy, dx): (i32, i32)| links.contains(&((y, x), (y + dy, x + dx)));
+        // corners with their outer and inner vertical and horizontal directions
+        let (y, x) = (3 * y as usize, 3 * x as usize);
+        for (fy, fx, side, tile) in [
+            (0, 1, n, '-'),
+            (2, 1, s, '-'),
+            (1, 0, w, '|'),
+            (1, 2, e, '|'),
+        ] {
+            if !linked(side) {
+                grid[y + fy][x + fx] = tile;
+            }
+        }
+        for (fy, fx, (outer_v, inner_v), (outer_h, inner_h)) in [
+            (0, 0, (n, s), (w, e)),
+            (0, 2, (n, s), (e, w)),
+            (2, 0, (s, n), (w, e)),
+            (2, 2, (s, n), (e, w)),
+        ] {
+            let a = if linked(outer_v) { outer_v } else { inner_h };
+            let b = if linked(outer_h) { outer_h } else { inner_v };
+            let tile = match (a.0 + b.0, a.1 + b.1) {
+                (0, 0) if a.0 == 0 => '-',
+                (0, 0) => '|',
+                (-1, 1) => 'L',
+                (-1, -1) => 'J',
+                (1, -1) => '7',
+                _ => 'F',
+            };
+            grid[y + fy][x + fx] = tile;
+        }
+    }
+
+    let starts = (0..grid.len())
+        .flat_map(|y| (0..grid.len()).map(move |x| (y, x)))
+        .filter(|&(y, x)| grid[y][x] != '.')
+        .collect::<Vec<(usize, usize)>>();
+    let (sy, sx) = starts[rng.gen_range(0..starts.len())];
+    grid[sy][sx] = 'S';
+
+    // junk pipes, away from the starting point
+    for (y, line) in grid.iter_mut().enumerate() {
+        for (x, tile) in line.iter_mut().enumerate() {
+            if *tile == '.' && sy.abs_diff(y) + sx.abs_diff(x) > 1 && rng.gen_bool(0.3) {
+                *tile = ['|', '-', 'L', 'J', '7', 'F'][rng.gen_range(0..6)];
+            }
+        }
+    }
+
+    grid.iter().map(|line| line.iter().collect()).collect()
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse,
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", process)],
+            vec![
+                Implementation::new("ray-casting", process2),
+                Implementation::new("shoelace", process2_shoelace),
+            ],
+        ],
+        inspectors: vec![],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::day10::generate;
     use crate::day10::process;
     use crate::day10::process2;
+    use crate::day10::process2_shoelace;
+    use crate::day10::validate;
 
     #[test]
     fn test1() {
@@ -198,7 +427,7 @@ mod tests {
             "|F--J".into(),
             "LJ...".into(),
         ];
-        assert!(process(input).unwrap() == 8);
+        assert!(process(&input).unwrap() == 8);
     }
 
     #[test]
@@ -210,7 +439,7 @@ mod tests {
             "|F--J".into(),
             "LJ.LJ".into(),
         ];
-        assert!(process(input).unwrap() == 8);
+        assert!(process(&input).unwrap() == 8);
     }
 
     #[test]
@@ -226,7 +455,8 @@ mod tests {
             ".L--J.L--J.".into(),
             "...........".into(),
         ];
-        assert!(process2(input).unwrap() == 4);
+        assert!(process2(&input).unwrap() == 4);
+        assert!(process2_shoelace(&input).unwrap() == 4);
     }
 
     #[test]
@@ -243,7 +473,8 @@ mod tests {
             "....FJL-7.||.||||...".into(),
             "....L---J.LJ.LJLJ...".into(),
         ];
-        assert!(process2(input).unwrap() == 8);
+        assert!(process2(&input).unwrap() == 8);
+        assert!(process2_shoelace(&input).unwrap() == 8);
     }
 
     #[test]
@@ -260,6 +491,63 @@ mod tests {
             "L.L7LFJ|||||FJL7||LJ".into(),
             "L7JLJL-JLJLJL--JLJ.L".into(),
         ];
-        assert!(process2(input).unwrap() == 10);
+        assert!(process2(&input).unwrap() == 10);
+        assert!(process2_shoelace(&input).unwrap() == 10);
+    }
+
+    #[test]
+    fn test6() {
+        let input = vec!["S-7".to_string(), "|.|".into(), "L-S".into(), "..".into()];
+        let violations = validate(&input);
+        assert!(violations.len() == 2);
+        assert!(violations[0].line == 4 && violations[0].column == 3);
+        assert!(violations[1].line == 3 && violations[1].column == 3 && violations[1].text == "S");
+    }
+
+    #[test]
+    fn test7() {
+        for seed in 0..20 {
+            let input = generate(&mut StdRng::seed_from_u64(seed), 30);
+            assert!(process2(&input).unwrap() == process2_shoelace(&input).unwrap());
+        }
+    }
+
+    #[test]
+    fn test8() {
+        // starting point on a pipe connecting to the south
+        let input = vec![
+            ".....".to_string(),
+            ".F-7.".into(),
+            ".S.|.".into(),
+            ".L-J.".into(),
+            ".....".into(),
+        ];
+        assert!(process2(&input).unwrap() == 1 && process2_shoelace(&input).unwrap() == 1);
+
+        let input = vec!["S7".to_string(), "LJ".into()];
+        assert!(process2(&input).unwrap() == 0);
+    }
+
+    #[test]
+    fn test9() {
+        let ragged = vec!["S-7".to_string(), "|.|".into(), "L-J".into(), ".".into()];
+        let empty = Vec::new();
+        for input in [ragged, empty] {
+            let error = validate(&input)[0].to_string();
+            assert!(process(&input).unwrap_err().to_string() == error);
+            assert!(process2(&input).unwrap_err().to_string() == error);
+            assert!(process2_shoelace(&input).unwrap_err().to_string() == error);
+        }
+        let input = vec!["Sé-".to_string(), "|.|".into()];
+        assert!(process(&input)
+            .unwrap_err()
+            .to_string()
+            .starts_with("error: unknown tile"));
+
+        // same located error as validation when the start is missing
+        let input = vec!["F-7".to_string(), "L-J".into()];
+        let error = validate(&input)[0].to_string();
+        assert!(process(&input).unwrap_err().to_string() == error);
+        assert!(process2_shoelace(&input).unwrap_err().to_string() == error);
     }
 }
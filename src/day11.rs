@@ -1,13 +1,22 @@
 use anyhow::Result;
 use num::abs;
+use rand::{rngs::StdRng, Rng};
 
-use crate::lines_from_file;
+use crate::{
+    arith::{self, Answer},
+    error::ParseError,
+    parallel,
+    registry::{Implementation, Inspector, Parameter, Solution},
+    validate,
+};
+
+const DAY: u32 = 11;
 
 fn line_is_empty(line: &str) -> bool {
     line.chars().all(|c| c == '.')
 }
 
-fn col_is_empty(input: &Vec<String>, col: i32) -> bool {
+fn col_is_empty(input: &[String], col: i32) -> bool {
     input
         .iter()
         .all(|line| line.chars().nth(col as usize).unwrap() == '.')
@@ -24,27 +33,54 @@ fn process_distance(
 
     for i in y1..y2 {
         if empty_lines.contains(&i) {
-            dist += incr;
+            dist = arith::add(dist, incr)?;
         }
     }
 
     for i in x1..x2 {
         if empty_cols.contains(&i) {
-            dist += incr;
+            dist = arith::add(dist, incr)?;
         }
     }
 
     for i in x2..x1 {
         if empty_cols.contains(&i) {
-            dist += incr;
+            dist = arith::add(dist, incr)?;
         }
     }
 
     Ok(dist)
 }
 
-fn process(input: Vec<String>, incr: i64) -> Result<i64> {
-    let galaxies: Vec<(i32, i32)> = input
+/// Lines and columns without galaxies, which expand.
+fn find_empty(input: &[String]) -> Result<(Vec<i32>, Vec<i32>)> {
+    // columns are only defined when all lines have the same length
+    if let Some(error) = validate::rectangular(DAY, input).into_iter().next() {
+        return Err(error.into());
+    }
+    let empty_lines: Vec<i32> = input
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line_is_empty(line))
+        .map(|(y, _)| y as i32)
+        .collect();
+
+    let empty_cols: Vec<i32> = (0..input[0].chars().count() as i32)
+        .filter(|col| col_is_empty(input, *col))
+        .collect();
+
+    Ok((empty_lines, empty_cols))
+}
+
+/// Positions of the galaxies, line by line.
+fn parse_galaxies(input: &[String]) -> Result<Vec<(i32, i32)>> {
+    for (i, line) in input.iter().enumerate() {
+        if let Some((x, c)) = line.char_indices().find(|(_, c)| *c != '.' && *c != '#') {
+            return Err(ParseError::new(DAY, i, line, x..x + c.len_utf8(), "unknown tile").into());
+        }
+    }
+
+    Ok(input
         .iter()
         .enumerate()
         .flat_map(|(y, line)| {
@@ -53,40 +89,104 @@ fn process(input: Vec<String>, incr: i64) -> Result<i64> {
                 .filter(|(_, c)| *c == '#')
                 .map(move |(x, _)| (x as i32, y as i32))
         })
-        .collect();
-
-    let empty_lines: Vec<i32> = input
-        .iter()
-        .enumerate()
-        .filter(|(_, line)| line_is_empty(line))
-        .map(|(y, _)| y as i32)
-        .collect();
+        .collect())
+}
 
-    let empty_cols: Vec<i32> = (0..input[0].len() as i32)
-        .filter(|col| col_is_empty(&input, *col))
-        .collect();
+fn process(input: &[String], incr: i64) -> Result<Answer> {
+    let galaxies = parse_galaxies(input)?;
+    let (empty_lines, empty_cols) = find_empty(input)?;
 
-    let mut res = 0;
-    for i in 0..galaxies.len() {
-        let (x1, y1) = galaxies[i];
+    // sum the distances from each galaxy to the following ones
+    let sums = parallel::map(&galaxies, |i, &(x1, y1)| -> Result<i128> {
+        let mut sum = 0;
         for (x2, y2) in galaxies.iter().skip(i + 1) {
-            res += process_distance((x1, y1), (*x2, *y2), &empty_lines, &empty_cols, incr)?;
+            let dist = process_distance((x1, y1), (*x2, *y2), &empty_lines, &empty_cols, incr)?;
+            sum = arith::add(sum, dist as i128)?;
         }
-    }
+        Ok(sum)
+    });
 
+    let mut res = Answer::zero();
+    for sum in sums {
+        res.add(sum?)?;
+    }
     Ok(res)
 }
 
-pub fn run() {
-    let res = process(lines_from_file("./input/day11.txt"), 1);
-    println!(" * {:?}", res.unwrap());
-    let res = process(lines_from_file("./input/day11.txt"), 999999);
-    println!(" * {:?}", res.unwrap());
+fn show_expansion(input: &[String]) -> Result<String> {
+    if input.is_empty() {
+        return Ok("empty image".to_string());
+    }
+    let (empty_lines, empty_cols) = find_empty(input)?;
+    Ok(format!(
+        "empty lines: {:?}\nempty columns: {:?}",
+        empty_lines, empty_cols
+    ))
+}
+
+/// Galaxies and the lines and columns expanding between them.
+fn parse(input: &[String]) -> Result<()> {
+    parse_galaxies(input)?;
+    find_empty(input)?;
+    Ok(())
+}
+
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    let mut violations = validate::rectangular(DAY, input);
+    violations.extend(validate::tiles(DAY, input, |c| c == '.' || c == '#'));
+    violations
+}
+
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.gen_bool(0.05) { '#' } else { '.' })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse,
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", |input| process(input, 1))],
+            vec![Implementation::new("default", |input| {
+                process(input, 999999)
+            })],
+        ],
+        inspectors: vec![Inspector {
+            name: "expansion",
+            show: show_expansion,
+        }],
+        // lines added for each empty line or column
+        parameters: vec![
+            Parameter {
+                name: "incr",
+                part: 0,
+                implementation: "default",
+                default: 1,
+                solve: |input, incr| process(input, incr),
+            },
+            Parameter {
+                name: "incr",
+                part: 1,
+                implementation: "default",
+                default: 999999,
+                solve: |input, incr| process(input, incr),
+            },
+        ],
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day11::process;
+    use crate::day11::validate;
 
     #[test]
     fn test1() {
@@ -102,7 +202,7 @@ mod tests {
             ".......#..".into(),
             "#...#.....".into(),
         ];
-        assert!(process(input, 1).unwrap() == 374);
+        assert!(process(&input, 1).unwrap() == 374);
     }
 
     #[test]
@@ -119,6 +219,16 @@ mod tests {
             ".......#..".into(),
             "#...#.....".into(),
         ];
-        assert!(process(input, 99).unwrap() == 8410);
+        assert!(process(&input, 99).unwrap() == 8410);
+    }
+
+    #[test]
+    fn test3() {
+        let ragged = vec!["#..".to_string(), "..".into(), "..#".into()];
+        let empty = Vec::new();
+        for input in [ragged, empty] {
+            let error = validate(&input)[0].to_string();
+            assert!(process(&input, 1).unwrap_err().to_string() == error);
+        }
     }
 }
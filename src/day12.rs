@@ -1,8 +1,50 @@
-use std::{collections::HashMap, time::Instant};
+use std::collections::HashMap;
 
 use anyhow::Result;
+use rand::{rngs::StdRng, Rng};
 
-use crate::lines_from_file;
+use crate::{
+    arith::{self, Answer},
+    error::{parse_token, ParseError},
+    parallel,
+    registry::{Implementation, Solution},
+};
+
+const DAY: u32 = 12;
+
+fn parse_record(index: usize, line: &str) -> Result<(&str, Vec<usize>), ParseError> {
+    let Some((spring, group)) = line.split_once(' ') else {
+        return Err(ParseError::line(
+            DAY,
+            index,
+            line,
+            "expected `<springs> <groups>`",
+        ));
+    };
+    if let Some((i, c)) = spring.char_indices().find(|(_, c)| !".#?".contains(*c)) {
+        return Err(ParseError::new(
+            DAY,
+            index,
+            line,
+            i..i + c.len_utf8(),
+            "unknown spring condition",
+        ));
+    }
+    let group = group
+        .split(',')
+        .map(|s| parse_token(DAY, index, line, s))
+        .collect::<Result<Vec<usize>, ParseError>>()?;
+    Ok((spring, group))
+}
+
+fn parse_records(input: &[String]) -> Result<(Vec<&str>, Vec<Vec<usize>>), ParseError> {
+    let records = input
+        .iter()
+        .enumerate()
+        .map(|(i, line)| parse_record(i, line))
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    Ok(records.into_iter().unzip())
+}
 
 fn process_spring(
     spring: String,
@@ -31,7 +73,10 @@ fn process_spring(
     }
 
     if ".?".contains(spring.chars().next().unwrap()) {
-        res += process_spring(spring.chars().skip(1).collect::<String>(), group, cache)?;
+        res = arith::add(
+            res,
+            process_spring(spring.chars().skip(1).collect::<String>(), group, cache)?,
+        )?;
     }
 
     if "#?".contains(spring.chars().next().unwrap())
@@ -39,10 +84,13 @@ fn process_spring(
         && !&spring[..group[0]].contains('.')
         && (group[0] == spring.len() || spring.chars().nth(group[0]).unwrap() != '#')
     {
-        res += process_spring(
-            spring.chars().skip(group[0] + 1).collect::<String>(),
-            &group[1..].to_vec(),
-            cache,
+        res = arith::add(
+            res,
+            process_spring(
+                spring.chars().skip(group[0] + 1).collect::<String>(),
+                &group[1..].to_vec(),
+                cache,
+            )?,
         )?;
     }
 
@@ -50,48 +98,31 @@ fn process_spring(
     Ok(res)
 }
 
-fn process(input: Vec<String>) -> Result<i64> {
-    let mut cache: HashMap<(String, Vec<usize>), i64> = HashMap::new();
-
-    let springs = input
-        .iter()
-        .map(|line| line.split(' ').collect::<Vec<&str>>()[0])
-        .collect::<Vec<&str>>();
-    let groups = input
-        .iter()
-        .map(|line| {
-            line.split(' ').collect::<Vec<&str>>()[1]
-                .split(',')
-                .map(|s| s.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>()
-        })
-        .collect::<Vec<Vec<usize>>>();
+/// Count the arrangements of each record, each one with its own cache so that
+/// records can be processed in parallel.
+fn count_arrangements(springs: &[String], groups: &[Vec<usize>]) -> Result<Answer> {
+    let counts = parallel::map(springs, |i, spring| {
+        process_spring(spring.to_string(), &groups[i], &mut HashMap::new())
+    });
 
-    let mut total = 0;
-    for it in springs.iter().zip(groups.iter()) {
-        let (spring, group) = it;
-        total += process_spring(spring.to_string(), group, &mut cache)?;
+    let mut total = Answer::zero();
+    for count in counts {
+        total.add(count?)?;
     }
-
     Ok(total)
 }
 
-fn process2(input: Vec<String>) -> Result<i64> {
-    let mut cache: HashMap<(String, Vec<usize>), i64> = HashMap::new();
-
-    let springs = input
-        .iter()
-        .map(|line| line.split(' ').collect::<Vec<&str>>()[0])
-        .collect::<Vec<&str>>();
-    let mut groups = input
+fn process(input: &[String]) -> Result<Answer> {
+    let (springs, groups) = parse_records(input)?;
+    let springs = springs
         .iter()
-        .map(|line| {
-            line.split(' ').collect::<Vec<&str>>()[1]
-                .split(',')
-                .map(|s| s.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>()
-        })
-        .collect::<Vec<Vec<usize>>>();
+        .map(|s| s.to_string())
+        .collect::<Vec<String>>();
+    count_arrangements(&springs, &groups)
+}
+
+fn process2(input: &[String]) -> Result<Answer> {
+    let (springs, mut groups) = parse_records(input)?;
 
     // unfold data
     let new_springs = springs
@@ -103,29 +134,62 @@ fn process2(input: Vec<String>) -> Result<i64> {
         .map(|g| g.repeat(5))
         .collect::<Vec<Vec<usize>>>();
 
-    let mut total = 0;
-    for it in new_springs.iter().zip(groups.iter()) {
-        let (spring, group) = it;
-        total += process_spring(spring.to_string(), group, &mut cache)?;
-    }
+    count_arrangements(&new_springs, &groups)
+}
 
-    Ok(total)
+pub fn validate(input: &[String]) -> Vec<ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| parse_record(i, line).err())
+        .collect()
 }
 
-pub fn run() {
-    let start = Instant::now();
-    let res = process(lines_from_file("./input/day12.txt"));
-    let duration = start.elapsed();
-    println!(" * {:?} (computed in {:?})", res.unwrap(), duration);
+pub fn generate(rng: &mut StdRng, size: usize) -> Vec<String> {
+    (0..size)
+        .map(|_| {
+            // pick an arrangement, then hide some of the springs
+            let mut springs = (0..rng.gen_range(1..=20))
+                .map(|_| if rng.gen_bool(0.5) { '#' } else { '.' })
+                .collect::<Vec<char>>();
+            let len = springs.len();
+            springs[rng.gen_range(0..len)] = '#';
 
-    let start = Instant::now();
-    let res = process2(lines_from_file("./input/day12.txt"));
-    let duration = start.elapsed();
-    println!(" * {:?} (computed in {:?})", res.unwrap(), duration);
+            let group = springs
+                .split(|c| *c == '.')
+                .filter(|g| !g.is_empty())
+                .map(|g| g.len().to_string())
+                .collect::<Vec<String>>();
+            let spring = springs
+                .iter()
+                .map(|&c| if rng.gen_bool(0.5) { '?' } else { c })
+                .collect::<String>();
+
+            format!("{} {}", spring, group.join(","))
+        })
+        .collect()
+}
+
+pub fn solution() -> Solution {
+    Solution {
+        day: DAY,
+        validate: |input, _| validate(input),
+        parse: |input| Ok(parse_records(input).map(|_| ())?),
+        generate,
+        parts: vec![
+            vec![Implementation::new("default", process)],
+            vec![Implementation::new("default", process2)],
+        ],
+        inspectors: vec![],
+        parameters: vec![],
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::day12::generate;
     use crate::day12::process;
     use crate::day12::process2;
 
@@ -139,7 +203,7 @@ mod tests {
             "????.######..#####. 1,6,5".into(),
             "?###???????? 3,2,1".into(),
         ];
-        assert!(process(input).unwrap() == 21);
+        assert!(process(&input).unwrap() == 21);
     }
 
     #[test]
@@ -152,6 +216,47 @@ mod tests {
             "????.######..#####. 1,6,5".into(),
             "?###???????? 3,2,1".into(),
         ];
-        assert!(process2(input).unwrap() == 525152);
+        assert!(process2(&input).unwrap() == 525152);
+    }
+
+    #[test]
+    fn test3() {
+        // reference implementation trying every arrangement
+        fn brute_force(spring: &str, group: &[usize]) -> i64 {
+            let unknowns = spring
+                .match_indices('?')
+                .map(|(i, _)| i)
+                .collect::<Vec<usize>>();
+            let mut count = 0;
+            for mask in 0..1 << unknowns.len() {
+                let mut row = spring.chars().collect::<Vec<char>>();
+                for (bit, &i) in unknowns.iter().enumerate() {
+                    row[i] = if mask & (1 << bit) != 0 { '#' } else { '.' };
+                }
+                let groups = row
+                    .split(|c| *c == '.')
+                    .filter(|g| !g.is_empty())
+                    .map(|g| g.len())
+                    .collect::<Vec<usize>>();
+                count += (groups == group) as i64;
+            }
+            count
+        }
+
+        for seed in 0..20 {
+            let input = generate(&mut StdRng::seed_from_u64(seed), 10);
+            let expected = input
+                .iter()
+                .map(|line| {
+                    let (spring, group) = line.split_once(' ').unwrap();
+                    let group = group
+                        .split(',')
+                        .map(|g| g.parse().unwrap())
+                        .collect::<Vec<usize>>();
+                    brute_force(spring, &group)
+                })
+                .sum::<i64>();
+            assert!(process(&input).unwrap() == expected);
+        }
     }
 }
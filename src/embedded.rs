@@ -0,0 +1,40 @@
+/// Copy of an input compiled into the binary, kept encrypted when only its
+/// encrypted copy existed at build time.
+#[derive(Clone, Copy, Debug)]
+// variants are only built by the generated list, which may not need both
+#[allow(dead_code)]
+pub enum Embedded {
+    Plain(&'static str),
+    Encrypted(&'static [u8]),
+}
+
+/// Inputs and examples of each day compiled into the binary, so that days can
+/// be solved from any working directory. The list is written by the build script.
+#[cfg(feature = "embed")]
+const INPUTS: [(Embedded, Embedded); 12] = include!(concat!(env!("OUT_DIR"), "/inputs.rs"));
+
+/// Embedded input of a day, or its example.
+#[cfg(feature = "embed")]
+pub fn input(day: u32, example: bool) -> Option<Embedded> {
+    let (input, example_input) = INPUTS.get((day as usize).checked_sub(1)?)?;
+    Some(if example { *example_input } else { *input })
+}
+
+#[cfg(not(feature = "embed"))]
+pub fn input(_day: u32, _example: bool) -> Option<Embedded> {
+    None
+}
+
+#[cfg(all(test, feature = "embed"))]
+mod tests {
+    use crate::embedded::{input, Embedded};
+
+    #[test]
+    fn test1() {
+        assert!(
+            matches!(input(11, true), Some(Embedded::Plain(text)) if text.starts_with("...#......\n"))
+        );
+        assert!(input(1, false).is_some() && input(12, false).is_some());
+        assert!(input(0, false).is_none() && input(13, true).is_none());
+    }
+}
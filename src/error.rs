@@ -0,0 +1,121 @@
+use std::{fmt, ops::Range, str::FromStr};
+
+/// Malformed puzzle input, located by day, line and column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u32,
+    /// Line number, starting at 1
+    pub line: usize,
+    /// Column number in characters, starting at 1
+    pub column: usize,
+    /// Offending text
+    pub text: String,
+    /// Whole input line containing the offending text
+    pub source: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Build an error from the byte span of the offending text in `line`.
+    pub fn new(
+        day: u32,
+        index: usize,
+        line: &str,
+        span: Range<usize>,
+        message: impl Into<String>,
+    ) -> Self {
+        let start = span.start.min(line.len());
+        let end = span.end.clamp(start, line.len());
+        ParseError {
+            day,
+            line: index + 1,
+            column: line[..start].chars().count() + 1,
+            text: line[start..end].to_string(),
+            source: line.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Build an error pointing at `token`, which should be a slice of `line`.
+    pub fn at(day: u32, index: usize, line: &str, token: &str, message: impl Into<String>) -> Self {
+        let base = line.as_ptr() as usize;
+        let ptr = token.as_ptr() as usize;
+        let start = if ptr >= base && ptr + token.len() <= base + line.len() {
+            ptr - base
+        } else {
+            line.find(token).unwrap_or(0)
+        };
+        ParseError::new(day, index, line, start..start + token.len(), message)
+    }
+
+    /// Build an error covering the whole line.
+    pub fn line(day: u32, index: usize, line: &str, message: impl Into<String>) -> Self {
+        ParseError::new(day, index, line, 0..line.len(), message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        let carets = "^".repeat(self.text.chars().count().max(1));
+
+        writeln!(f, "error: {}", self.message)?;
+        writeln!(
+            f,
+            "{}--> day{:02}:{}:{}",
+            gutter, self.day, self.line, self.column
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", self.line, self.source)?;
+        write!(f, "{} | {}{}", gutter, " ".repeat(self.column - 1), carets)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse `token`, a slice of `line`, reporting a located error on failure.
+pub fn parse_token<T: FromStr>(
+    day: u32,
+    index: usize,
+    line: &str,
+    token: &str,
+) -> Result<T, ParseError> {
+    token.parse::<T>().map_err(|_| {
+        ParseError::at(
+            day,
+            index,
+            line,
+            token,
+            format!("invalid number `{}`", token),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{parse_token, ParseError};
+
+    #[test]
+    fn test1() {
+        let line = "Game 3: x blue";
+        let err = ParseError::at(2, 2, line, &line[8..9], "invalid number `x`");
+        assert!(err.column == 9);
+        assert!(
+            err.to_string()
+                == "error: invalid number `x`\n \
+                    --> day02:3:9\n  \
+                    |\n\
+                    3 | Game 3: x blue\n  \
+                    |         ^"
+        );
+    }
+
+    #[test]
+    fn test2() {
+        let line = "0 3 six 9";
+        let token = line.split_whitespace().nth(2).unwrap();
+        let err = parse_token::<i32>(9, 11, line, token).unwrap_err();
+        assert!(err.line == 12 && err.column == 5 && err.text == "six");
+        assert!(err.to_string().ends_with("   |     ^^^"));
+    }
+}
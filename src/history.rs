@@ -0,0 +1,307 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    process::Command,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+pub const HISTORY_FILE: &str = "./history.jsonl";
+
+/// Timing of a part, as stored in the history file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// Unix time of the run, in milliseconds, shared by all parts of a run
+    pub run: u64,
+    /// Abbreviated hash of the checked out commit
+    pub commit: String,
+    /// Whether the tree had local changes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dirty: bool,
+    pub day: u32,
+    pub part: usize,
+    pub implementation: String,
+    pub nanos: u64,
+    /// Answer found by the part, missing from records stored by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+}
+
+impl Record {
+    pub fn new(
+        run: u64,
+        commit: &str,
+        day: u32,
+        part: usize,
+        implementation: &str,
+        duration: Duration,
+    ) -> Self {
+        Record {
+            run,
+            commit: commit.to_string(),
+            dirty: false,
+            day,
+            part,
+            implementation: implementation.to_string(),
+            nanos: duration.as_nanos() as u64,
+            answer: None,
+        }
+    }
+}
+
+/// Identifier of the current run.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Trimmed output of a successful git command.
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Abbreviated hash of the checked out commit, and whether the tree has local changes.
+pub fn current_commit() -> (String, bool) {
+    let Some(commit) = git(&["rev-parse", "--short", "HEAD"]) else {
+        return ("unknown".to_string(), false);
+    };
+    let dirty =
+        git(&["status", "--porcelain", "--untracked-files=no"]).is_some_and(|s| !s.is_empty());
+    (commit, dirty)
+}
+
+/// Full hash of a commit given by any name git knows, like an abbreviation or a tag,
+/// or the name itself when git cannot resolve it.
+pub fn resolve(commit: &str) -> String {
+    git(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^{{commit}}", commit),
+    ])
+    .unwrap_or_else(|| commit.to_string())
+}
+
+/// Whether a recorded commit and a given one are abbreviations of the same hash.
+fn same_commit(recorded: &str, given: &str) -> bool {
+    !recorded.is_empty()
+        && !given.is_empty()
+        && (recorded.starts_with(given) || given.starts_with(recorded))
+}
+
+pub fn append(path: impl AsRef<Path>, records: &[Record]) -> Result<()> {
+    let path = path.as_ref();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("cannot open {}", path.display()))?;
+    // single write, so that days run in parallel do not interleave their records
+    let mut lines = String::new();
+    for record in records {
+        lines += &serde_json::to_string(record)?;
+        lines.push('\n');
+    }
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<Record>> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(i, line)| {
+            serde_json::from_str(&line?)
+                .with_context(|| format!("{}:{}: invalid record", path.display(), i + 1))
+        })
+        .collect()
+}
+
+/// Records of the last run of a day.
+pub fn last_run(records: &[Record], day: u32) -> Vec<Record> {
+    let day_records = records.iter().filter(|r| r.day == day);
+    let Some(run) = day_records.clone().map(|r| r.run).max() else {
+        return Vec::new();
+    };
+    day_records.filter(|r| r.run == run).cloned().collect()
+}
+
+/// Best timing of each part and implementation among `records`.
+fn best<'a>(records: impl Iterator<Item = &'a Record>) -> BTreeMap<(usize, &'a str), u64> {
+    let mut best = BTreeMap::new();
+    for record in records {
+        best.entry((record.part, record.implementation.as_str()))
+            .and_modify(|nanos: &mut u64| *nanos = (*nanos).min(record.nanos))
+            .or_insert(record.nanos);
+    }
+    best
+}
+
+fn change(before: u64, after: u64) -> String {
+    if before == 0 || after == 0 {
+        return "n/a".to_string();
+    }
+    match after.cmp(&before) {
+        std::cmp::Ordering::Less => format!("{:.2}x faster", before as f64 / after as f64),
+        std::cmp::Ordering::Greater => format!("{:.2}x slower", after as f64 / before as f64),
+        std::cmp::Ordering::Equal => "unchanged".to_string(),
+    }
+}
+
+/// Compare the best timings of a day between two commits.
+pub fn compare_commits(records: &[Record], day: u32, from: &str, to: &str) -> Vec<String> {
+    let before = best(
+        records
+            .iter()
+            .filter(|r| r.day == day && same_commit(&r.commit, from)),
+    );
+    let after = best(
+        records
+            .iter()
+            .filter(|r| r.day == day && same_commit(&r.commit, to)),
+    );
+
+    let mut lines = Vec::new();
+    for ((part, implementation), nanos) in &after {
+        let line = match before.get(&(*part, *implementation)) {
+            Some(previous) => format!(
+                " * part {} ({}): {:?} -> {:?}, {}",
+                part + 1,
+                implementation,
+                Duration::from_nanos(*previous),
+                Duration::from_nanos(*nanos),
+                change(*previous, *nanos)
+            ),
+            None => format!(
+                " * part {} ({}): {:?}, not run at {}",
+                part + 1,
+                implementation,
+                Duration::from_nanos(*nanos),
+                from
+            ),
+        };
+        lines.push(line);
+    }
+    lines
+}
+
+/// List the last `count` runs of a day, comparing each one with the previous.
+pub fn compare_last(records: &[Record], day: u32, count: usize) -> Vec<String> {
+    let mut runs: BTreeMap<u64, Vec<&Record>> = BTreeMap::new();
+    for record in records.iter().filter(|r| r.day == day) {
+        runs.entry(record.run).or_default().push(record);
+    }
+    let runs = runs.into_values().collect::<Vec<Vec<&Record>>>();
+
+    let mut lines = Vec::new();
+    for (i, run) in runs
+        .iter()
+        .enumerate()
+        .skip(runs.len().saturating_sub(count))
+    {
+        let previous = i.checked_sub(1).map(|i| best(runs[i].iter().copied()));
+        let timings = best(run.iter().copied())
+            .iter()
+            .map(|((part, implementation), nanos)| {
+                let mut timing = format!(
+                    "part {} ({}) {:?}",
+                    part + 1,
+                    implementation,
+                    Duration::from_nanos(*nanos)
+                );
+                if let Some(previous) = previous
+                    .as_ref()
+                    .and_then(|p| p.get(&(*part, *implementation)))
+                {
+                    timing += &format!(" {}", change(*previous, *nanos));
+                }
+                timing
+            })
+            .collect::<Vec<String>>();
+        let dirty = if run.iter().any(|r| r.dirty) {
+            " (dirty)"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            " * {}{}: {}",
+            run[0].commit,
+            dirty,
+            timings.join(", ")
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::history::{compare_commits, compare_last, Record};
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record::new(1, "abc", 12, 0, "default", Duration::from_millis(20)),
+            Record::new(1, "abc", 12, 1, "default", Duration::from_millis(600)),
+            Record::new(2, "abc", 12, 1, "default", Duration::from_millis(500)),
+            Record::new(3, "def", 12, 0, "default", Duration::from_millis(10)),
+            Record::new(3, "def", 12, 1, "default", Duration::from_millis(750)),
+            Record::new(3, "def", 11, 0, "default", Duration::from_millis(1)),
+        ]
+    }
+
+    #[test]
+    fn test1() {
+        let record = &records()[0];
+        let line = serde_json::to_string(record).unwrap();
+        assert!(
+            line == r#"{"run":1,"commit":"abc","day":12,"part":0,"implementation":"default","nanos":20000000}"#
+        );
+        assert!(serde_json::from_str::<Record>(&line).unwrap() == *record);
+    }
+
+    #[test]
+    fn test2() {
+        let lines = compare_commits(&records(), 12, "abc", "def");
+        assert!(lines.len() == 2);
+        assert!(lines[0] == " * part 1 (default): 20ms -> 10ms, 2.00x faster");
+        assert!(lines[1] == " * part 2 (default): 500ms -> 750ms, 1.50x slower");
+    }
+
+    #[test]
+    fn test3() {
+        let lines = compare_last(&records(), 12, 2);
+        assert!(lines.len() == 2);
+        assert!(lines[0] == " * abc: part 2 (default) 500ms 1.20x faster");
+        assert!(lines[1] == " * def: part 1 (default) 10ms, part 2 (default) 750ms 1.50x slower");
+    }
+
+    #[test]
+    fn test4() {
+        // any abbreviation of the recorded hash selects its records
+        let mut records = records();
+        records
+            .iter_mut()
+            .for_each(|r| r.commit = format!("{}1234", r.commit));
+        records[3].dirty = true;
+        let lines = compare_commits(&records, 12, "abc12", "def1234567890");
+        assert!(lines.len() == 2 && lines[0] == " * part 1 (default): 20ms -> 10ms, 2.00x faster");
+        assert!(compare_commits(&records, 12, "abd", "def")
+            .iter()
+            .all(|line| line.ends_with("not run at abd")));
+        assert!(compare_last(&records, 12, 1) == [" * def1234 (dirty): part 1 (default) 10ms, part 2 (default) 750ms 1.50x slower"]);
+    }
+}
@@ -0,0 +1,222 @@
+use std::{collections::HashMap, fs, path::Path, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// Private leaderboard, as exported by `/<year>/leaderboard/private/view/<id>.json`.
+#[derive(Debug, Deserialize)]
+pub struct Leaderboard {
+    pub event: String,
+    pub members: HashMap<String, Member>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u32,
+    /// Stars of each day and part, indexed by their number as a string
+    #[serde(default)]
+    pub completion_day_level: HashMap<String, HashMap<String, Star>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Star {
+    pub get_star_ts: i64,
+}
+
+impl Member {
+    pub fn name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    /// Unix time at which the part of the day was solved.
+    pub fn star(&self, day: u32, part: u32) -> Option<i64> {
+        let star = self
+            .completion_day_level
+            .get(&day.to_string())?
+            .get(&part.to_string())?;
+        Some(star.get_star_ts)
+    }
+}
+
+pub fn parse(json: &str) -> Result<Leaderboard> {
+    Ok(serde_json::from_str(json)?)
+}
+
+pub fn load(path: impl AsRef<Path>) -> Result<Leaderboard> {
+    let path = path.as_ref();
+    let json =
+        fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    parse(&json).with_context(|| format!("invalid leaderboard in {}", path.display()))
+}
+
+/// Download the leaderboard, authenticated with the session cookie of a member.
+pub fn fetch(url: &str, session: &str, timeout: Duration) -> Result<Leaderboard> {
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    let response = match agent
+        .get(url)
+        .set("Cookie", &format!("session={}", session))
+        .call()
+    {
+        Ok(response) => response,
+        Err(ureq::Error::Status(code, _)) => bail!("{} answered with status {}", url, code),
+        Err(e) => return Err(e).with_context(|| format!("cannot fetch {}", url)),
+    };
+    let json = response.into_string()?;
+    parse(&json).with_context(|| {
+        format!(
+            "invalid leaderboard at {}, is the session still valid?",
+            url
+        )
+    })
+}
+
+/// Members sorted by decreasing local score, then stars.
+pub fn rankings(leaderboard: &Leaderboard) -> Vec<&Member> {
+    let mut members = leaderboard.members.values().collect::<Vec<&Member>>();
+    members.sort_by(|a, b| {
+        b.local_score
+            .cmp(&a.local_score)
+            .then(b.stars.cmp(&a.stars))
+            .then(a.id.cmp(&b.id))
+    });
+    members
+}
+
+/// Number of days from 1970-01-01 to a date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Unix time at which a day is unlocked, midnight in the US Eastern time zone.
+pub fn unlock(year: i64, day: u32) -> i64 {
+    (days_from_civil(year, 12, day as i64) * 24 + 5) * 3600
+}
+
+fn duration(seconds: i64) -> String {
+    if seconds >= 24 * 3600 {
+        return format!(">{}h", seconds / 3600);
+    }
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+pub fn print_rankings(leaderboard: &Leaderboard) -> Vec<String> {
+    rankings(leaderboard)
+        .iter()
+        .enumerate()
+        .map(|(i, member)| {
+            format!(
+                "{:>3}) {:>4} {:>3}* {}",
+                i + 1,
+                member.local_score,
+                member.stars,
+                member.name()
+            )
+        })
+        .collect()
+}
+
+/// Time each member took to solve both parts of a day since it was unlocked,
+/// and the time spent on part 2.
+pub fn print_day(leaderboard: &Leaderboard, day: u32) -> Result<Vec<String>> {
+    let year = leaderboard
+        .event
+        .parse::<i64>()
+        .context("invalid event year")?;
+    let unlock = unlock(year, day);
+
+    let mut members = rankings(leaderboard)
+        .into_iter()
+        .filter_map(|m| Some((m, m.star(day, 1)?, m.star(day, 2))))
+        .collect::<Vec<(&Member, i64, Option<i64>)>>();
+    members.sort_by_key(|(_, part1, part2)| (part2.unwrap_or(i64::MAX), *part1));
+
+    Ok(members
+        .iter()
+        .map(|(member, part1, part2)| {
+            let part2 = match part2 {
+                Some(part2) => format!(
+                    "{} (+{})",
+                    duration(part2 - unlock),
+                    duration(part2 - part1)
+                ),
+                None => "-".to_string(),
+            };
+            format!(
+                " * {:>10}  {:<22} {}",
+                duration(part1 - unlock),
+                part2,
+                member.name()
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::leaderboard::{parse, print_day, print_rankings, unlock};
+
+    const EXPORT: &str = r#"{
+        "event": "2023",
+        "owner_id": 1,
+        "members": {
+            "1": {
+                "id": 1, "name": "alice", "stars": 3, "local_score": 7, "global_score": 0,
+                "last_star_ts": 1701410000,
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": 1701407100, "star_index": 10},
+                        "2": {"get_star_ts": 1701407700, "star_index": 12}
+                    },
+                    "2": {"1": {"get_star_ts": 1701494000, "star_index": 20}}
+                }
+            },
+            "2": {
+                "id": 2, "name": null, "stars": 2, "local_score": 4, "global_score": 0,
+                "last_star_ts": 1701409000,
+                "completion_day_level": {
+                    "1": {
+                        "1": {"get_star_ts": 1701406900, "star_index": 9},
+                        "2": {"get_star_ts": 1701410500, "star_index": 13}
+                    }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test1() {
+        // 2023-12-01 00:00:00 EST
+        assert!(unlock(2023, 1) == 1701406800);
+        assert!(unlock(2024, 25) == 1735102800);
+    }
+
+    #[test]
+    fn test2() {
+        let leaderboard = parse(EXPORT).unwrap();
+        let rankings = print_rankings(&leaderboard);
+        assert!(rankings == ["  1)    7   3* alice", "  2)    4   2* (anonymous user #2)"]);
+
+        let day = print_day(&leaderboard, 1).unwrap();
+        assert!(day[0] == " *   00:05:00  00:15:00 (+00:10:00)   alice");
+        assert!(day[1] == " *   00:01:40  01:01:40 (+01:00:00)   (anonymous user #2)");
+
+        let day = print_day(&leaderboard, 2).unwrap();
+        assert!(day == [" *   00:13:20  -                      alice"]);
+    }
+}
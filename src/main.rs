@@ -1,10 +1,25 @@
-use clap::Parser;
+use anyhow::{bail, Context, Result};
+use arith::{Answer, Precision};
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand};
+use config::{Color, Config, Format};
+use embedded::Embedded;
+use error::ParseError;
+use memory::CountingAllocator;
+use output::Output;
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::*;
+use registry::{Implementation, Solution};
 use std::{
-    fs::File,
+    collections::HashSet,
+    fs::{self, File},
     io::{BufRead, BufReader},
-    path::Path,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
 
+mod arith;
+mod config;
+mod crypto;
 mod day01;
 mod day02;
 mod day03;
@@ -17,13 +32,154 @@ mod day09;
 mod day10;
 mod day11;
 mod day12;
+mod embedded;
+mod error;
+mod history;
+mod leaderboard;
+mod memory;
+mod output;
+mod parallel;
+mod registry;
+mod repl;
+mod report;
+mod server;
+mod setting;
+mod validate;
 
-fn lines_from_file(filename: impl AsRef<Path>) -> Vec<String> {
-    let file = File::open(filename).expect("no such file");
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn lines_from_file(filename: impl AsRef<Path>) -> Result<Vec<String>> {
+    let filename = filename.as_ref();
+    let file =
+        File::open(filename).with_context(|| format!("cannot open {}", filename.display()))?;
     let buf = BufReader::new(file);
-    buf.lines()
-        .map(|l| l.expect("Could not parse line"))
-        .collect()
+    Ok(buf.lines().collect::<Result<Vec<String>, _>>()?)
+}
+
+/// Lines of the file at `path`, decrypted from its encrypted copy when the file
+/// is missing.
+fn read_file(settings: &Config, path: &Path) -> Result<Vec<String>> {
+    let encrypted = crypto::encrypted_path(path);
+    if !path.exists() && encrypted.exists() {
+        let text = crypto::read(&settings.key()?, &encrypted)?;
+        return Ok(text.lines().map(String::from).collect());
+    }
+    lines_from_file(path)
+}
+
+/// Input of a day, or its example, read from the input directory. When the
+/// file and its encrypted copy are missing, builds with the `embed` feature fall
+/// back to their own copy.
+fn read_input(settings: &Config, day: u32, example: bool) -> Result<Vec<String>> {
+    let path = settings.input_path(day, example);
+    if path.exists() || crypto::encrypted_path(&path).exists() {
+        return read_file(settings, &path);
+    }
+    let text = match embedded::input(day, example) {
+        Some(Embedded::Plain(text)) => text.to_string(),
+        Some(Embedded::Encrypted(data)) => {
+            crypto::decrypt(&settings.key()?, data).context("cannot decrypt the embedded input")?
+        }
+        None => return lines_from_file(path),
+    };
+    Ok(text.lines().map(String::from).collect())
+}
+
+/// Time and memory spent by a step.
+#[derive(Clone, Copy)]
+struct Cost {
+    duration: Duration,
+    memory: Option<memory::Stats>,
+}
+
+impl std::fmt::Display for Cost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "computed in {:?}", self.duration)?;
+        if let Some(memory) = &self.memory {
+            write!(f, ", {}", memory)?;
+        }
+        Ok(())
+    }
+}
+
+impl Cost {
+    /// Cost of a step once that of a step it includes, like parsing, is taken out.
+    fn without(&self, other: &Cost) -> Cost {
+        Cost {
+            duration: self.duration.saturating_sub(other.duration),
+            memory: self
+                .memory
+                .zip(other.memory)
+                .map(|(memory, other)| memory.without(&other)),
+        }
+    }
+}
+
+fn measure<T>(f: impl FnOnce() -> T) -> (T, Cost) {
+    let start = Instant::now();
+    let (res, memory) = memory::measure(f);
+    let duration = start.elapsed();
+    (res, Cost { duration, memory })
+}
+
+fn print_answer(
+    out: &mut Output,
+    format: Format,
+    (day, part): (u32, usize),
+    res: Result<Answer>,
+    cost: Cost,
+) {
+    match (res, format) {
+        (Ok(res), Format::Text) => {
+            out.println(format!(" {} {} ({})", output::paint("*", 33), res, cost))
+        }
+        (Ok(res), Format::Json) => out.println(
+            serde_json::json!({
+                "day": day,
+                "part": part + 1,
+                "answer": res.to_string(),
+                "nanos": cost.duration.as_nanos() as u64,
+            })
+            .to_string(),
+        ),
+        (Err(e), _) => out.eprintln(output::paint(&e.to_string(), 31)),
+    }
+}
+
+fn solve(implementation: &Implementation, input: &[String]) -> (Result<Answer>, Cost) {
+    measure(|| (implementation.solve)(input))
+}
+
+/// Run every implementation of a part, returning whether they all agree. Costs
+/// leave out `parse` when given, since each implementation parses the input again.
+fn crosscheck(
+    out: &mut Output,
+    implementations: &[Implementation],
+    input: &[String],
+    parse: Option<&Cost>,
+) -> bool {
+    let mut answers = Vec::new();
+    for implementation in implementations {
+        let (res, cost) = solve(implementation, input);
+        let cost = parse.map_or(cost, |parse| cost.without(parse));
+        match &res {
+            Ok(res) => out.println(format!("   - {}: {} ({})", implementation.name, res, cost)),
+            Err(e) => out.eprintln(format!("   - {}: {}", implementation.name, e)),
+        }
+        answers.push(res.ok());
+    }
+
+    if answers.iter().any(Option::is_none) {
+        out.println("   implementations failed");
+        return false;
+    }
+
+    let agree = answers.iter().all(|a| *a == answers[0]);
+    if !agree {
+        out.println("   implementations disagree");
+    }
+    agree
 }
 
 /// Advent of code 2023
@@ -31,28 +187,800 @@ fn lines_from_file(filename: impl AsRef<Path>) -> Vec<String> {
 struct Arguments {
     #[clap(short, long)]
     /// Index of the day
-    day: i32,
+    day: Option<u32>,
+
+    #[clap(long, conflicts_with = "day")]
+    /// Solve all covered days
+    all: bool,
+
+    #[clap(short, long, default_value_t = 1, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..), conflicts_with = "memory")]
+    /// Number of threads solving days and the records of days supporting it
+    jobs: usize,
+
+    #[clap(long)]
+    /// Check the input against what each part requires and report all violations before solving it
+    validate: bool,
+
+    #[clap(long, value_enum, default_value_t)]
+    /// Integer width used to accumulate answers
+    precision: Precision,
+
+    #[clap(long = "impl")]
+    /// Name of the implementation to use for parts providing several
+    implementation: Option<String>,
+
+    #[clap(long)]
+    /// Run all implementations of each part and check they agree
+    crosscheck: bool,
+
+    #[clap(long)]
+    /// Report the memory allocated while reading, then parsing the input, then while solving each part, parsing left out
+    memory: bool,
+
+    #[clap(long, global = true)]
+    /// Solve the example of the puzzle statement instead of the input
+    example: bool,
+
+    #[clap(long, global = true)]
+    /// File of `token = value` lines recognized as digits by day 1, besides 0 to 9
+    vocabulary: Option<PathBuf>,
+
+    #[clap(long, global = true)]
+    /// Cubes in the bag of day 2, like `12 red, 13 green, 14 blue`
+    bag: Option<String>,
+
+    #[clap(long, global = true, conflicts_with = "bag")]
+    /// File holding the cubes in the bag of day 2
+    bag_file: Option<PathBuf>,
+
+    #[clap(long, global = true)]
+    /// Gears of day 3 and their ratio, as `<symbols>:<count>:<aggregate>` like `#$:>=3:sum`
+    gear: Option<day03::GearRule>,
+
+    #[clap(long)]
+    /// Do not append the timings of this run to the history file
+    no_history: bool,
+
+    #[clap(long, global = true)]
+    /// Configuration file, instead of ./aoc.toml or the user configuration directory
+    config: Option<PathBuf>,
+
+    #[clap(long, global = true)]
+    /// Directory holding the `dayNN.txt` inputs
+    input_dir: Option<PathBuf>,
+
+    #[clap(long, global = true)]
+    /// Year of the puzzles
+    year: Option<u32>,
+
+    #[clap(long, global = true)]
+    /// File holding the session cookie, used when AOC_SESSION is not set
+    session_file: Option<PathBuf>,
+
+    #[clap(long, global = true)]
+    /// File holding the passphrase of encrypted inputs, used when AOC_INPUT_KEY is not set
+    key_file: Option<PathBuf>,
+
+    #[clap(long, value_enum, global = true)]
+    /// How answers are printed
+    format: Option<Format>,
+
+    #[clap(long, value_enum, global = true)]
+    /// When to color the output
+    color: Option<Color>,
+
+    #[clap(long, global = true)]
+    /// Timeout of network requests, in seconds
+    timeout: Option<u64>,
+
+    #[clap(skip)]
+    /// Settings of the configuration file, overridden by the options above
+    settings: Config,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print a random input for the day
+    Generate {
+        #[clap(long, default_value_t = 10)]
+        /// Size of the input, in lines or grid side depending on the day
+        size: usize,
+
+        #[clap(long)]
+        /// Seed of the random generator, picked at random when missing
+        seed: Option<u64>,
+    },
+    /// Cross-check and time all implementations on random inputs of growing size
+    Bench {
+        #[clap(long, value_delimiter = ',', default_value = "10,50,100")]
+        /// Sizes of the generated inputs, in lines or grid sides, the defaults being solved quickly by every day
+        sizes: Vec<usize>,
+
+        #[clap(long)]
+        /// Seed of the random generator, picked at random when missing
+        seed: Option<u64>,
+    },
+    /// Write a Markdown table of the answers and timings of each day, or of the one given with --day
+    Report {
+        #[clap(long)]
+        /// Use the last results stored in the history file instead of solving the days
+        cached: bool,
+
+        #[clap(long)]
+        /// Hide the answers
+        redact: bool,
+
+        #[clap(long)]
+        /// File to write the table to, instead of the standard output
+        output: Option<PathBuf>,
+    },
+    /// Show the rankings and star times of a private leaderboard
+    Leaderboard {
+        #[clap(long, conflicts_with = "url")]
+        /// Leaderboard JSON export
+        file: Option<PathBuf>,
+
+        #[clap(long)]
+        /// URL of the leaderboard JSON export, defaulting to the configured one
+        url: Option<String>,
+    },
+    /// Print a structure of the day built from its input, or list them
+    Show {
+        /// Name of the structure
+        name: Option<String>,
+    },
+    /// Explore the input of the day interactively
+    Repl,
+    /// Solve day 1 reading its input line by line, in constant memory
+    Stream {
+        /// File to read, the standard input when missing
+        file: Option<PathBuf>,
+
+        #[clap(long, default_value_t = 1_000_000)]
+        /// Number of lines between progress reports, 0 to only report at the end
+        every: u64,
+    },
+    /// Serve the solvers over HTTP, on `POST /solve/{day}/{part}` with the input as body
+    Serve {
+        #[clap(long, default_value = "127.0.0.1:8023")]
+        /// Address to listen on
+        addr: String,
+    },
+    /// Infer the bags of day 2 consistent with the games
+    Bags {
+        #[clap(long)]
+        /// Number of cubes in the bag, printing the fewest cubes per game when missing
+        total: Option<u32>,
+
+        #[clap(long, value_delimiter = ',')]
+        /// Ids of the games the bag must make possible, all of them when missing
+        games: Vec<u32>,
+
+        #[clap(long, default_value_t = 10)]
+        /// Number of bags to list
+        limit: usize,
+    },
+    /// Print statistics of the day 2 games against the bag
+    Stats,
+    /// Inspect the configuration
+    Config {
+        #[clap(subcommand)]
+        action: ConfigAction,
+    },
+    /// Compare the timings stored in the history file
+    Compare {
+        #[clap(num_args = 0..=2)]
+        /// Commits to compare, by hash, abbreviation or any name git knows, the second one defaulting to the checked out commit
+        commits: Vec<String>,
+
+        #[clap(long, default_value_t = 5)]
+        /// Number of runs to list when no commit is given
+        last: usize,
+    },
+    /// Replace the input of the day by a copy encrypted with the input key
+    Encrypt {
+        #[clap(long)]
+        /// Keep the plain text input
+        keep: bool,
+    },
+    /// Restore the plain text input of the day from its encrypted copy
+    Decrypt {
+        #[clap(long)]
+        /// Keep the encrypted copy
+        keep: bool,
+    },
+}
+
+impl Command {
+    /// Day the command is specific to, and the error printed for other days.
+    fn day(&self) -> Option<(u32, &'static str)> {
+        match self {
+            Command::Stream { .. } => Some((1, "Streaming is only supported by day 1")),
+            Command::Bags { .. } => Some((2, "Bag inference is only supported by day 2")),
+            Command::Stats => Some((2, "Statistics are only supported by day 2")),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigAction {
+    /// Print the effective settings
+    Show,
+}
+
+impl Arguments {
+    /// Load the configuration file, then apply the options overriding it.
+    fn configure(&mut self) -> Result<Option<PathBuf>> {
+        let (mut settings, path) = Config::find(self.config.as_deref())?;
+        if let Some(input_dir) = &self.input_dir {
+            settings.input_dir = input_dir.clone();
+        }
+        if let Some(year) = self.year {
+            settings.year = year;
+        }
+        if let Some(session_file) = &self.session_file {
+            settings.session_file = Some(session_file.clone());
+        }
+        if let Some(key_file) = &self.key_file {
+            settings.key_file = Some(key_file.clone());
+        }
+        if let Some(format) = self.format {
+            settings.format = format;
+        }
+        if let Some(color) = self.color {
+            settings.color = color;
+        }
+        if let Some(timeout) = self.timeout {
+            settings.timeout = timeout;
+        }
+        self.settings = settings;
+        Ok(path)
+    }
+}
+
+fn seed(seed: Option<u64>) -> u64 {
+    let seed = seed.unwrap_or_else(rand::random);
+    eprintln!("seed: {}", seed);
+    seed
+}
+
+fn bench(out: &mut Output, solution: &Solution, sizes: &[usize], rng: &mut StdRng) -> bool {
+    let mut agree = true;
+    for &size in sizes {
+        let input = (solution.generate)(rng, size);
+        for (part, implementations) in solution.parts.iter().enumerate() {
+            out.println(format!(" * size {}, part {}", size, part + 1));
+            agree &= crosscheck(out, implementations, &input, None);
+        }
+    }
+    agree
+}
+
+/// Solve each part of a day, returning whether it succeeded along with the stored records.
+fn run(out: &mut Output, args: &Arguments, solution: &Solution) -> (bool, Vec<history::Record>) {
+    let (input, cost) = measure(|| read_input(&args.settings, solution.day, args.example));
+    let input = match input {
+        Ok(input) => input,
+        Err(e) => {
+            out.eprintln(output::paint(&format!("{:#}", e), 31));
+            return (false, Vec::new());
+        }
+    };
+    if args.memory {
+        out.println(format!(" - read {} lines of input ({})", input.len(), cost));
+    }
+    // parts parse the input again, the cost of parsing is taken out of theirs
+    let parse = match args.memory {
+        true => match measure(|| (solution.parse)(&input)) {
+            (Ok(()), cost) => {
+                out.println(format!(" - parsed the input ({})", cost));
+                Some(cost)
+            }
+            (Err(e), _) => {
+                out.eprintln(output::paint(&format!("{:#}", e), 31));
+                None
+            }
+        },
+        false => None,
+    };
+
+    let mut agree = true;
+    let mut records = Vec::new();
+    let mut reported = HashSet::new();
+    let (run, (commit, dirty)) = (history::now(), history::current_commit());
+    for (part, implementations) in solution.parts.iter().enumerate() {
+        if args.validate {
+            // violations shared by the parts are only printed once
+            let violations = (solution.validate)(&input, part);
+            let unseen = violations
+                .iter()
+                .filter(|v| reported.insert(v.to_string()))
+                .cloned();
+            validate::report(out, &unseen.collect::<Vec<ParseError>>());
+            if !violations.is_empty() {
+                agree = false;
+                continue;
+            }
+        }
+        if args.crosscheck {
+            out.println(format!(" * part {}", part + 1));
+            agree &= crosscheck(out, implementations, &input, parse.as_ref());
+        } else {
+            let implementation = solution.implementation(part, args.implementation.as_deref());
+            let (res, cost) = solve(implementation, &input);
+            agree &= res.is_ok();
+            if let Ok(res) = &res {
+                let mut record = history::Record::new(
+                    run,
+                    &commit,
+                    solution.day,
+                    part,
+                    implementation.name,
+                    cost.duration,
+                );
+                record.answer = Some(res.to_string());
+                record.dirty = dirty;
+                records.push(record);
+            }
+            let cost = parse.as_ref().map_or(cost, |parse| cost.without(parse));
+            print_answer(out, args.settings.format, (solution.day, part), res, cost);
+        }
+    }
+
+    if !args.no_history && !args.example && !records.is_empty() {
+        if let Err(e) = history::append(history::HISTORY_FILE, &records) {
+            out.eprintln(format!("Could not store timings: {:#}", e));
+        }
+    }
+    (agree, records)
+}
+
+fn compare(out: &mut Output, day: u32, commits: &[String], last: usize) -> bool {
+    let records = match history::load(history::HISTORY_FILE) {
+        Ok(records) => records,
+        Err(e) => {
+            out.eprintln(format!("{:#}", e));
+            return false;
+        }
+    };
+
+    let lines = match commits {
+        [] => history::compare_last(&records, day, last),
+        [from] => history::compare_commits(
+            &records,
+            day,
+            &history::resolve(from),
+            &history::current_commit().0,
+        ),
+        [from, to, ..] => history::compare_commits(
+            &records,
+            day,
+            &history::resolve(from),
+            &history::resolve(to),
+        ),
+    };
+    if lines.is_empty() {
+        out.println(" * no timings recorded");
+    }
+    for line in lines {
+        out.println(line);
+    }
+    true
+}
+
+/// Sum the calibration values of day 1 from a file or the standard input,
+/// reporting progress on the standard error.
+fn stream(file: Option<&Path>, every: u64) -> Result<Answer> {
+    let progress = |p: &day01::Progress| {
+        eprintln!(
+            " - {} lines, {:.1} MiB read, running sum {}",
+            p.lines,
+            p.bytes as f64 / (1 << 20) as f64,
+            p.sum
+        )
+    };
+    match file {
+        Some(path) => {
+            let file =
+                File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+            day01::process_stream(BufReader::new(file), every, progress)
+        }
+        None => day01::process_stream(std::io::stdin().lock(), every, progress),
+    }
+}
+
+/// Print the structure `name` of a day, or the names of its structures.
+fn show(out: &mut Output, args: &Arguments, solution: &Solution, name: Option<&str>) -> bool {
+    let Some(name) = name else {
+        for inspector in &solution.inspectors {
+            out.println(format!(" * {}", inspector.name));
+        }
+        return true;
+    };
+    let Some(inspector) = solution.inspectors.iter().find(|i| i.name == name) else {
+        out.eprintln(format!("No structure named `{}`", name));
+        return false;
+    };
+    match read_input(&args.settings, solution.day, args.example)
+        .and_then(|input| (inspector.show)(&input))
+    {
+        Ok(text) => out.println(text),
+        Err(e) => {
+            out.eprintln(output::paint(&format!("{:#}", e), 31));
+            return false;
+        }
+    }
+    true
+}
+
+/// Print the bags of `total` cubes consistent with the day 2 games, or the fewest
+/// cubes of each game when `total` is missing.
+fn bags(
+    out: &mut Output,
+    args: &Arguments,
+    total: Option<u32>,
+    games: &[u32],
+    limit: usize,
+) -> bool {
+    let res = read_input(&args.settings, 2, args.example).and_then(|input| match total {
+        Some(total) => Ok(day02::infer(&input, total, games, limit)?.join("\n")),
+        None => day02::show_bags(&input),
+    });
+    match res {
+        Ok(text) => out.println(text),
+        Err(e) => {
+            out.eprintln(output::paint(&format!("{:#}", e), 31));
+            return false;
+        }
+    }
+    true
+}
+
+/// Print the statistics of the day 2 games against the bag.
+fn stats(out: &mut Output, args: &Arguments) -> bool {
+    let res = read_input(&args.settings, 2, args.example)
+        .and_then(|input| day02::stats(&input, day02::BAG.get()));
+    match (res, args.settings.format) {
+        (Ok(stats), Format::Text) => out.println(stats.to_string()),
+        (Ok(stats), Format::Json) => {
+            out.println(serde_json::to_string(&stats).expect("statistics are serializable"))
+        }
+        (Err(e), _) => {
+            out.eprintln(output::paint(&format!("{:#}", e), 31));
+            return false;
+        }
+    }
+    true
+}
+
+/// Encrypt the input of a day, or restore it from its encrypted copy.
+fn crypt(out: &mut Output, settings: &Config, day: u32, encrypt: bool, keep: bool) -> bool {
+    let path = settings.input(day);
+    let res = settings.key().and_then(|key| match encrypt {
+        true => crypto::encrypt_file(&key, &path, keep),
+        false => crypto::decrypt_file(&key, &path, keep),
+    });
+    match res {
+        Ok(encrypted) if encrypt => out.println(format!(
+            " * encrypted {} to {}",
+            path.display(),
+            encrypted.display()
+        )),
+        Ok(encrypted) => out.println(format!(
+            " * decrypted {} to {}",
+            encrypted.display(),
+            path.display()
+        )),
+        Err(e) => {
+            out.eprintln(output::paint(&format!("{:#}", e), 31));
+            return false;
+        }
+    }
+    true
+}
+
+/// Run the command on a day, returning whether it succeeded along with its output.
+fn day(args: &Arguments, solution: &Solution, seed: u64) -> (bool, Output) {
+    let mut out = Output::default();
+    if args.settings.format == Format::Text {
+        out.println(format!("Day {}:", solution.day));
+    }
+    let ok = match &args.command {
+        Some(Command::Bench { sizes, .. }) => {
+            bench(&mut out, solution, sizes, &mut StdRng::seed_from_u64(seed))
+        }
+        Some(Command::Compare { commits, last }) => compare(&mut out, solution.day, commits, *last),
+        Some(Command::Show { name }) => show(&mut out, args, solution, name.as_deref()),
+        Some(Command::Encrypt { keep }) => {
+            crypt(&mut out, &args.settings, solution.day, true, *keep)
+        }
+        Some(Command::Decrypt { keep }) => {
+            crypt(&mut out, &args.settings, solution.day, false, *keep)
+        }
+        Some(Command::Stream { file, every }) => {
+            let (res, cost) = measure(|| stream(file.as_deref(), *every));
+            let ok = res.is_ok();
+            print_answer(&mut out, args.settings.format, (1, 0), res, cost);
+            ok
+        }
+        Some(Command::Bags {
+            total,
+            games,
+            limit,
+        }) => bags(&mut out, args, *total, games, *limit),
+        Some(Command::Stats) => stats(&mut out, args),
+        _ => run(&mut out, args, solution).0,
+    };
+    (ok, out)
+}
+
+/// Write a Markdown table of the results of each day, solving them unless `cached`.
+fn report(
+    args: &Arguments,
+    solutions: &[Solution],
+    cached: bool,
+    redact: bool,
+    output: Option<&Path>,
+) -> bool {
+    let (success, records) = match cached {
+        true => match history::load(history::HISTORY_FILE) {
+            Ok(records) => (true, records),
+            Err(e) => {
+                eprintln!("{:#}", e);
+                return false;
+            }
+        },
+        false => {
+            let runs = solutions
+                .par_iter()
+                .map(|solution| {
+                    let mut out = Output::default();
+                    let (success, records) = run(&mut out, args, solution);
+                    (out, success, records)
+                })
+                .collect::<Vec<_>>();
+            let mut success = true;
+            let mut records = Vec::new();
+            for (out, ok, run) in runs {
+                out.flush_errors();
+                success &= ok;
+                records.extend(run);
+            }
+            (success, records)
+        }
+    };
+
+    let days = solutions
+        .iter()
+        .map(|s| report::DayReport::new(s.day, s.parts.len(), &history::last_run(&records, s.day)))
+        .collect::<Vec<report::DayReport>>();
+    let table = report::markdown(&days, redact);
+
+    match output {
+        Some(path) => match fs::write(path, table) {
+            Ok(()) => success,
+            Err(e) => {
+                eprintln!("Could not write {}: {}", path.display(), e);
+                false
+            }
+        },
+        None => {
+            print!("{}", table);
+            success
+        }
+    }
+}
+
+/// Print the rankings, then the times of each day or of the selected one.
+fn leaderboard(
+    settings: &Config,
+    file: Option<&Path>,
+    url: Option<&str>,
+    day: Option<u32>,
+) -> Result<()> {
+    let leaderboard = match (file, url.or(settings.leaderboard.as_deref())) {
+        (Some(file), _) => leaderboard::load(file)?,
+        (None, Some(url)) => leaderboard::fetch(url, &settings.session()?, settings.timeout())?,
+        (None, None) => bail!("No leaderboard file given nor URL configured"),
+    };
+
+    println!("Leaderboard {}", leaderboard.event);
+    for line in leaderboard::print_rankings(&leaderboard) {
+        println!("{}", line);
+    }
+
+    let days = match day {
+        Some(day) => day..=day,
+        None => 1..=25,
+    };
+    for day in days {
+        let lines = leaderboard::print_day(&leaderboard, day)?;
+        if !lines.is_empty() {
+            println!("Day {}:", day);
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+    }
+    Ok(())
 }
 
 fn main() {
-    let args = Arguments::parse();
-
-    println!("Advent of code 2023");
-    println!("Day {}:", &args.day);
-
-    match args.day {
-        1 => day01::run(),
-        2 => day02::run(),
-        3 => day03::run(),
-        4 => day04::run(),
-        5 => day05::run(),
-        6 => day06::run(),
-        7 => day07::run(),
-        8 => day08::run(),
-        9 => day09::run(),
-        10 => day10::run(),
-        11 => day11::run(),
-        12 => day12::run(),
-        _ => println!("Day {} not covered yet...", args.day),
+    let mut args = Arguments::parse();
+
+    let config = match args.configure() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+    };
+    output::set_color(args.settings.use_color());
+
+    if let Some(Command::Config {
+        action: ConfigAction::Show,
+    }) = &args.command
+    {
+        match config {
+            Some(path) => println!("# loaded from {}", path.display()),
+            None => println!("# no configuration file found, using defaults"),
+        }
+        print!("{}", args.settings.show());
+        return;
+    }
+
+    if let Some(path) = &args.vocabulary {
+        match day01::Vocabulary::load(path) {
+            Ok(vocabulary) => day01::VOCABULARY.set(vocabulary),
+            Err(e) => {
+                eprintln!("{:#}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let bag = match (&args.bag, &args.bag_file) {
+        (Some(bag), _) => Some(day02::Bag::parse(bag).context("invalid bag")),
+        (None, Some(path)) => Some(day02::Bag::load(path)),
+        (None, None) => None,
+    };
+    match bag {
+        Some(Ok(bag)) => day02::BAG.set(bag),
+        Some(Err(e)) => {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        None => {}
+    }
+
+    if let Some(rule) = &args.gear {
+        day03::GEAR_RULE.set(rule.clone());
+    }
+
+    if let Some(Command::Serve { addr }) = &args.command {
+        arith::set_precision(args.precision);
+        if let Err(e) = server::serve(addr) {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Leaderboard { file, url }) = &args.command {
+        if let Err(e) = leaderboard(&args.settings, file.as_deref(), url.as_deref(), args.day) {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // the report covers all days unless one is given
+    let all = args.all || matches!(args.command, Some(Command::Report { .. }));
+    if args.day.is_none() && !all {
+        Arguments::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "either --day or --all is required",
+            )
+            .exit();
+    }
+
+    let solutions = match args.day {
+        Some(day) => match registry::get(day) {
+            Some(solution) => vec![solution],
+            None => {
+                println!("Day {} not covered yet...", day);
+                return;
+            }
+        },
+        None => registry::all(),
+    };
+
+    if let Some(name) = &args.implementation {
+        if !solutions.iter().any(|s| s.has_implementation(name)) {
+            eprintln!("No implementation named `{}`", name);
+            std::process::exit(1);
+        }
+    }
+
+    arith::set_precision(args.precision);
+    memory::set_enabled(args.memory);
+    parallel::set_jobs(args.jobs);
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.jobs)
+        .build_global()
+        .expect("Could not start threads");
+
+    if let Some(Command::Generate { size, seed }) = args.command {
+        let [solution] = &solutions[..] else {
+            eprintln!("Generating an input requires a single day");
+            std::process::exit(1);
+        };
+        for line in (solution.generate)(&mut StdRng::seed_from_u64(self::seed(seed)), size) {
+            println!("{}", line);
+        }
+        return;
+    }
+
+    if let Some(Command::Repl) = args.command {
+        let Ok([solution]) = <[Solution; 1]>::try_from(solutions) else {
+            eprintln!("The REPL requires a single day");
+            std::process::exit(1);
+        };
+        let res =
+            repl::Session::new(args.settings.clone(), solution, args.example).and_then(repl::repl);
+        if let Err(e) = res {
+            eprintln!("{:#}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some((day, error)) = args.command.as_ref().and_then(Command::day) {
+        if args.day != Some(day) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(Command::Report {
+        cached,
+        redact,
+        output,
+    }) = &args.command
+    {
+        if !report(&args, &solutions, *cached, *redact, output.as_deref()) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let seed = match &args.command {
+        Some(Command::Bench { seed, .. }) => self::seed(*seed),
+        _ => 0,
+    };
+
+    if args.settings.format == Format::Text {
+        println!("Advent of code {}", args.settings.year);
+    }
+
+    // days are solved in parallel, but printed in order
+    let results = solutions
+        .par_iter()
+        .map(|solution| day(&args, solution, seed))
+        .collect::<Vec<(bool, Output)>>();
+
+    let mut ok = true;
+    for (success, out) in results {
+        out.flush();
+        ok &= success;
+    }
+
+    if !ok {
+        std::process::exit(1);
     }
 }
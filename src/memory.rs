@@ -0,0 +1,175 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+/// System allocator counting allocations once enabled.
+pub struct CountingAllocator;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+impl CountingAllocator {
+    fn grow(size: usize) {
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        TOTAL.fetch_add(size, Ordering::Relaxed);
+        COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn shrink(size: usize) {
+        // memory allocated before counting was enabled may be freed afterwards
+        let _ = CURRENT.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| {
+            Some(x.saturating_sub(size))
+        });
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() && ENABLED.load(Ordering::Relaxed) {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        if ENABLED.load(Ordering::Relaxed) {
+            Self::shrink(layout.size());
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let ptr = System.realloc(ptr, layout, new_size);
+        if !ptr.is_null() && ENABLED.load(Ordering::Relaxed) {
+            Self::shrink(layout.size());
+            Self::grow(new_size);
+        }
+        ptr
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Memory allocated while running a step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Highest amount of memory in use at once, above what was in use at start
+    pub peak: usize,
+    /// Sum of the sizes of all allocations
+    pub total: usize,
+    /// Number of allocations, reallocations included
+    pub count: usize,
+}
+
+impl Stats {
+    /// Statistics of a step once those of a step it includes are taken out.
+    pub fn without(&self, other: &Stats) -> Stats {
+        Stats {
+            peak: self.peak.saturating_sub(other.peak),
+            total: self.total.saturating_sub(other.total),
+            count: self.count.saturating_sub(other.count),
+        }
+    }
+}
+
+/// Run `f`, collecting its memory statistics when counting is enabled.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<Stats>) {
+    if !enabled() {
+        return (f(), None);
+    }
+
+    let start = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(start, Ordering::Relaxed);
+    let total = TOTAL.load(Ordering::Relaxed);
+    let count = COUNT.load(Ordering::Relaxed);
+
+    let res = f();
+
+    let stats = Stats {
+        peak: PEAK.load(Ordering::Relaxed).saturating_sub(start),
+        total: TOTAL.load(Ordering::Relaxed) - total,
+        count: COUNT.load(Ordering::Relaxed) - count,
+    };
+    (res, Some(stats))
+}
+
+struct Bytes(usize);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+        let mut size = self.0 as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        match unit {
+            0 => write!(f, "{} B", self.0),
+            _ => write!(f, "{:.1} {}", size, UNITS[unit]),
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "peak {}, {} in {} allocations",
+            Bytes(self.peak),
+            Bytes(self.total),
+            self.count
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::{measure, set_enabled, Stats};
+
+    #[test]
+    fn test1() {
+        set_enabled(true);
+        let (v, stats) = measure(|| vec![0u64; 1000]);
+        let stats = stats.unwrap();
+        assert!(v.len() == 1000);
+        assert!(stats.count >= 1 && stats.total >= 8000 && stats.peak >= 8000);
+    }
+
+    #[test]
+    fn test2() {
+        let stats = Stats {
+            peak: 512,
+            total: 3 * 1024 * 1024 + 512 * 1024,
+            count: 42,
+        };
+        assert!(stats.to_string() == "peak 512 B, 3.5 MiB in 42 allocations");
+
+        let parse = Stats {
+            peak: 1024,
+            total: 1024 * 1024,
+            count: 2,
+        };
+        assert!(
+            stats.without(&parse)
+                == Stats {
+                    peak: 0,
+                    total: 2560 * 1024,
+                    count: 40
+                }
+        );
+    }
+}
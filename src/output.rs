@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+pub fn set_color(color: bool) {
+    COLOR.store(color, Ordering::Relaxed);
+}
+
+/// Wrap `text` in the ANSI escape sequence of the color `code` when colors are enabled.
+pub fn paint(text: &str, code: u8) -> String {
+    match COLOR.load(Ordering::Relaxed) {
+        true => format!("\x1b[{}m{}\x1b[0m", code, text),
+        false => text.to_string(),
+    }
+}
+
+/// Messages of a day, buffered so that days solved in parallel do not interleave.
+#[derive(Debug, Default)]
+pub struct Output {
+    messages: Vec<(bool, String)>,
+}
+
+impl Output {
+    pub fn println(&mut self, message: impl Into<String>) {
+        self.messages.push((false, message.into()));
+    }
+
+    pub fn eprintln(&mut self, message: impl Into<String>) {
+        self.messages.push((true, message.into()));
+    }
+
+    /// Print all messages, in order, to standard output or error.
+    pub fn flush(self) {
+        for (error, message) in self.messages {
+            match error {
+                true => eprintln!("{}", message),
+                false => println!("{}", message),
+            }
+        }
+    }
+
+    /// Print only the error messages, for commands whose standard output is a
+    /// document of their own.
+    pub fn flush_errors(self) {
+        for (_, message) in self.messages.into_iter().filter(|(error, _)| *error) {
+            eprintln!("{}", message);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::prelude::*;
+
+static JOBS: AtomicUsize = AtomicUsize::new(1);
+
+/// Number of threads solving days, also used by days iterating over independent records.
+pub fn set_jobs(jobs: usize) {
+    JOBS.store(jobs.max(1), Ordering::Relaxed);
+}
+
+pub fn jobs() -> usize {
+    JOBS.load(Ordering::Relaxed)
+}
+
+/// Apply `f` to every item along with its index, in parallel when several jobs
+/// are allowed. Results keep the order of `items`, so that aggregating them gives
+/// the same answer, or the same first error, whatever the number of jobs.
+pub fn map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &T) -> R + Sync + Send,
+{
+    match jobs() {
+        1 => items.iter().enumerate().map(|(i, x)| f(i, x)).collect(),
+        _ => items.par_iter().enumerate().map(|(i, x)| f(i, x)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parallel::{map, set_jobs};
+
+    #[test]
+    fn test1() {
+        let items = (0..1000).collect::<Vec<u64>>();
+        let expected = items.iter().map(|x| x * x).collect::<Vec<u64>>();
+        set_jobs(4);
+        assert!(map(&items, |i, x| x * i as u64) == expected);
+        set_jobs(1);
+        assert!(map(&items, |i, x| x * i as u64) == expected);
+    }
+}
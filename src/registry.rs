@@ -0,0 +1,131 @@
+use anyhow::Result;
+use rand::rngs::StdRng;
+
+use crate::{
+    arith::Answer, day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11,
+    day12, error::ParseError,
+};
+
+/// Named way of solving one part of a day.
+pub struct Implementation {
+    pub name: &'static str,
+    pub solve: fn(&[String]) -> Result<Answer>,
+}
+
+impl Implementation {
+    pub fn new(name: &'static str, solve: fn(&[String]) -> Result<Answer>) -> Self {
+        Implementation { name, solve }
+    }
+}
+
+/// Intermediate structure of a day, printed from the REPL.
+pub struct Inspector {
+    pub name: &'static str,
+    pub show: fn(&[String]) -> Result<String>,
+}
+
+/// Number a part depends on, which can be changed from the REPL.
+pub struct Parameter {
+    pub name: &'static str,
+    pub part: usize,
+    /// Name of the implementation of the part taking the parameter
+    pub implementation: &'static str,
+    pub default: i64,
+    /// Solve the part using the given value of the parameter
+    pub solve: fn(&[String], i64) -> Result<Answer>,
+}
+
+/// Entry point of a day: how to check and solve its input.
+pub struct Solution {
+    pub day: u32,
+    /// Violations of the input structure, and of what the part of the given index requires
+    pub validate: fn(&[String], usize) -> Vec<ParseError>,
+    /// Parse the input into what the parts start from, then drop it, to measure parsing on its own
+    pub parse: fn(&[String]) -> Result<()>,
+    /// Random valid input of the given size
+    pub generate: fn(&mut StdRng, usize) -> Vec<String>,
+    /// Implementations of each part, the first one being the default
+    pub parts: Vec<Vec<Implementation>>,
+    pub inspectors: Vec<Inspector>,
+    pub parameters: Vec<Parameter>,
+}
+
+impl Solution {
+    /// Pick the implementation called `name` for a part, falling back to its default one.
+    pub fn implementation(&self, part: usize, name: Option<&str>) -> &Implementation {
+        let implementations = &self.parts[part];
+        name.and_then(|name| implementations.iter().find(|i| i.name == name))
+            .unwrap_or(&implementations[0])
+    }
+
+    pub fn has_implementation(&self, name: &str) -> bool {
+        self.parts.iter().flatten().any(|i| i.name == name)
+    }
+}
+
+pub fn all() -> Vec<Solution> {
+    vec![
+        day01::solution(),
+        day02::solution(),
+        day03::solution(),
+        day04::solution(),
+        day05::solution(),
+        day06::solution(),
+        day07::solution(),
+        day08::solution(),
+        day09::solution(),
+        day10::solution(),
+        day11::solution(),
+        day12::solution(),
+    ]
+}
+
+pub fn get(day: u32) -> Option<Solution> {
+    all().into_iter().find(|s| s.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{config::Config, lines_from_file, registry::all};
+
+    #[test]
+    fn test1() {
+        // generated inputs must pass validation
+        for solution in all() {
+            for seed in 0..20 {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let input = (solution.generate)(&mut rng, 1 + seed as usize * 3);
+                for part in 0..solution.parts.len() {
+                    let violations = (solution.validate)(&input, part);
+                    assert!(
+                        violations.is_empty(),
+                        "day {} seed {}: {}",
+                        solution.day,
+                        seed,
+                        violations[0]
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test2() {
+        // example fixtures must pass validation and parse
+        for solution in all() {
+            let input = lines_from_file(Config::default().example(solution.day)).unwrap();
+            assert!((solution.parse)(&input).is_ok(), "day {}", solution.day);
+            for part in 0..solution.parts.len() {
+                let violations = (solution.validate)(&input, part);
+                assert!(
+                    violations.is_empty(),
+                    "day {}: {}",
+                    solution.day,
+                    violations[0]
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    io::{self, BufRead, Write},
+    path::PathBuf,
+    time::Instant,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{config::Config, read_file, read_input, registry::Solution};
+
+const HELP: &str = "\
+commands:
+  run [<part>] [<impl>]   solve all parts, or one part with an optional implementation
+  show [<name>]           list the structures of the day, or print one of them
+  params                  list the parameters and their values
+  set <name> <value>      change a parameter, `set <name>` restores its default
+  input [<count>]         print the first lines of the input
+  load [<path>]           reload the input, or load another file
+  help                    print this message
+  quit                    leave";
+
+/// Input of a day loaded once, explored through commands.
+pub struct Session {
+    settings: Config,
+    solution: Solution,
+    example: bool,
+    /// File loaded instead of the input of the day
+    path: Option<PathBuf>,
+    input: Vec<String>,
+    /// Current value of each parameter of the day
+    values: Vec<i64>,
+}
+
+impl Session {
+    /// Session on the input of the day, or its example.
+    pub fn new(settings: Config, solution: Solution, example: bool) -> Result<Self> {
+        let input = read_input(&settings, solution.day, example)?;
+        let values = solution.parameters.iter().map(|p| p.default).collect();
+        Ok(Session {
+            settings,
+            solution,
+            example,
+            path: None,
+            input,
+            values,
+        })
+    }
+
+    fn solve(&self, part: usize, name: Option<&str>) -> Result<String> {
+        let start = Instant::now();
+        let implementation = self.solution.implementation(part, name);
+        let parameter = self
+            .solution
+            .parameters
+            .iter()
+            .zip(&self.values)
+            .find(|(p, _)| p.part == part);
+        let res = match parameter {
+            Some((parameter, value)) if parameter.implementation == implementation.name => {
+                (parameter.solve)(&self.input, *value)
+            }
+            Some((parameter, value)) if *value != parameter.default => bail!(
+                "parameter `{}` only applies to implementation `{}` of part {}",
+                parameter.name,
+                parameter.implementation,
+                part + 1
+            ),
+            _ => (implementation.solve)(&self.input),
+        };
+        Ok(format!(
+            " * part {}: {} (computed in {:?})",
+            part + 1,
+            res?,
+            start.elapsed()
+        ))
+    }
+
+    fn run(&self, args: &[&str]) -> Result<String> {
+        let parts = match args.first() {
+            Some(part) => match part.parse::<usize>() {
+                Ok(part) if part >= 1 && part <= self.solution.parts.len() => vec![part - 1],
+                _ => bail!("no part {}", part),
+            },
+            None => (0..self.solution.parts.len()).collect(),
+        };
+        let name = args.get(1).copied();
+        if let Some(name) = name.filter(|name| !self.solution.has_implementation(name)) {
+            bail!("no implementation named `{}`", name);
+        }
+
+        let lines = parts
+            .into_iter()
+            .map(|part| self.solve(part, name))
+            .collect::<Result<Vec<String>>>()?;
+        Ok(lines.join("\n"))
+    }
+
+    fn show(&self, name: Option<&str>) -> Result<String> {
+        let Some(name) = name else {
+            let names = self
+                .solution
+                .inspectors
+                .iter()
+                .map(|i| i.name)
+                .collect::<Vec<&str>>();
+            return Ok(match names.is_empty() {
+                true => "no structures to show for this day".to_string(),
+                false => names.join(", "),
+            });
+        };
+        match self.solution.inspectors.iter().find(|i| i.name == name) {
+            Some(inspector) => (inspector.show)(&self.input),
+            None => bail!("no structure named `{}`", name),
+        }
+    }
+
+    fn params(&self) -> String {
+        if self.values.is_empty() {
+            return "no parameters for this day".to_string();
+        }
+        self.solution
+            .parameters
+            .iter()
+            .zip(&self.values)
+            .map(|(p, value)| {
+                format!(
+                    "{} = {} (part {}, default {})",
+                    p.name,
+                    value,
+                    p.part + 1,
+                    p.default
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn set(&mut self, name: &str, value: Option<&str>) -> Result<String> {
+        let value = value
+            .map(|v| {
+                v.parse::<i64>()
+                    .with_context(|| format!("invalid value `{}`", v))
+            })
+            .transpose()?;
+
+        let mut found = false;
+        for (parameter, current) in self.solution.parameters.iter().zip(self.values.iter_mut()) {
+            if parameter.name == name {
+                *current = value.unwrap_or(parameter.default);
+                found = true;
+            }
+        }
+        if !found {
+            bail!("no parameter named `{}`", name);
+        }
+        Ok(self.params())
+    }
+
+    fn load(&mut self, path: Option<&str>) -> Result<String> {
+        let path = path.map(PathBuf::from).or_else(|| self.path.clone());
+        self.input = match &path {
+            Some(path) => read_file(&self.settings, path)?,
+            None => read_input(&self.settings, self.solution.day, self.example)?,
+        };
+        self.path = path;
+        Ok(format!(
+            "loaded {} lines from {}",
+            self.input.len(),
+            self.source().display()
+        ))
+    }
+
+    /// Where the input was read from.
+    fn source(&self) -> PathBuf {
+        self.path
+            .clone()
+            .unwrap_or_else(|| self.settings.input_path(self.solution.day, self.example))
+    }
+
+    /// Execute a command line, returning what to print, or `None` to leave.
+    pub fn execute(&mut self, line: &str) -> Option<Result<String>> {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        let res = match words[..] {
+            [] => Ok(String::new()),
+            ["quit" | "exit"] => return None,
+            ["help"] => Ok(HELP.to_string()),
+            ["run", ref args @ ..] => self.run(args),
+            ["show"] => self.show(None),
+            ["show", name] => self.show(Some(name)),
+            ["params"] => Ok(self.params()),
+            ["set", name] => self.set(name, None),
+            ["set", name, value] => self.set(name, Some(value)),
+            ["input"] => Ok(self
+                .input
+                .iter()
+                .take(10)
+                .cloned()
+                .collect::<Vec<String>>()
+                .join("\n")),
+            ["input", count] => match count.parse::<usize>() {
+                Ok(count) => Ok(self
+                    .input
+                    .iter()
+                    .take(count)
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join("\n")),
+                Err(_) => Err(anyhow!("invalid count `{}`", count)),
+            },
+            ["load"] => self.load(None),
+            ["load", path] => self.load(Some(path)),
+            _ => Err(anyhow!("unknown command `{}`, try `help`", line.trim())),
+        };
+        Some(res)
+    }
+}
+
+/// Read commands from the standard input until `quit` or the end of input.
+pub fn repl(mut session: Session) -> Result<()> {
+    let day = session.solution.day;
+    println!(
+        "Day {}: {} lines loaded from {}, type `help` for the commands",
+        day,
+        session.input.len(),
+        session.source().display()
+    );
+
+    let stdin = io::stdin();
+    loop {
+        print!("day{:02}> ", day);
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(());
+        }
+        match session.execute(&line) {
+            None => return Ok(()),
+            Some(Ok(text)) if text.is_empty() => {}
+            Some(Ok(text)) => println!("{}", text),
+            Some(Err(e)) => eprintln!("{:#}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::Config, registry, repl::Session};
+
+    #[test]
+    fn test1() {
+        let mut session =
+            Session::new(Config::default(), registry::get(11).unwrap(), true).unwrap();
+
+        let run = |session: &mut Session, line| session.execute(line).unwrap().unwrap();
+        assert!(run(&mut session, "run 1").starts_with(" * part 1: 374 "));
+        assert!(run(&mut session, "set incr 9").starts_with("incr = 9 (part 1, default 1)"));
+        assert!(run(&mut session, "run 2").starts_with(" * part 2: 1030 "));
+        assert!(run(&mut session, "run 2 default").starts_with(" * part 2: 1030 "));
+        run(&mut session, "set incr");
+        assert!(run(&mut session, "run 2").starts_with(" * part 2: 82000210 "));
+        assert!(
+            run(&mut session, "show expansion") == "empty lines: [3, 7]\nempty columns: [2, 5, 8]"
+        );
+    }
+
+    #[test]
+    fn test2() {
+        let mut session =
+            Session::new(Config::default(), registry::get(11).unwrap(), true).unwrap();
+        assert!(session.execute("run 3").unwrap().is_err());
+        assert!(session.execute("set expansion 2").unwrap().is_err());
+        assert!(session.execute("set incr two").unwrap().is_err());
+        assert!(session.execute("frobnicate").unwrap().is_err());
+        assert!(session
+            .execute("load input/examples/missing.txt")
+            .unwrap()
+            .is_err());
+        assert!(
+            session.execute("load").unwrap().unwrap()
+                == "loaded 10 lines from ./input/examples/day11.txt"
+        );
+        assert!(session.execute("quit").is_none());
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use crate::history::Record;
+
+/// Results of a day, a part without record being unsolved.
+pub struct DayReport {
+    pub day: u32,
+    pub parts: Vec<Option<Record>>,
+}
+
+impl DayReport {
+    /// Pick the records of the `parts` parts of a day among `records`.
+    pub fn new(day: u32, parts: usize, records: &[Record]) -> Self {
+        DayReport {
+            day,
+            parts: (0..parts)
+                .map(|part| {
+                    records
+                        .iter()
+                        .find(|r| r.day == day && r.part == part)
+                        .cloned()
+                })
+                .collect(),
+        }
+    }
+
+    fn stars(&self) -> usize {
+        self.parts
+            .iter()
+            .filter(|r| r.as_ref().is_some_and(|r| r.answer.is_some()))
+            .count()
+    }
+
+    fn duration(&self) -> Duration {
+        self.parts
+            .iter()
+            .flatten()
+            .map(|r| Duration::from_nanos(r.nanos))
+            .sum()
+    }
+}
+
+fn cell(record: Option<&Record>, redact: bool) -> [String; 2] {
+    let Some((record, answer)) = record.and_then(|r| Some((r, r.answer.as_ref()?))) else {
+        return ["-".to_string(), "-".to_string()];
+    };
+    let answer = match redact {
+        true => "*redacted*".to_string(),
+        false => format!("`{}`", answer),
+    };
+    [answer, format!("{:?}", Duration::from_nanos(record.nanos))]
+}
+
+/// Markdown table of the answers and timings of each day, with a total row.
+pub fn markdown(days: &[DayReport], redact: bool) -> String {
+    let mut table = vec![
+        "| Day | Stars | Part 1 | Time | Part 2 | Time | Total |".to_string(),
+        "|----:|:------|-------:|-----:|-------:|-----:|------:|".to_string(),
+    ];
+
+    for day in days {
+        let mut row = vec![day.day.to_string(), "★".repeat(day.stars())];
+        for part in 0..2 {
+            row.extend(cell(day.parts.get(part).and_then(Option::as_ref), redact));
+        }
+        row.push(format!("{:?}", day.duration()));
+        table.push(format!("| {} |", row.join(" | ")));
+    }
+
+    let stars = days.iter().map(DayReport::stars).sum::<usize>();
+    let total = days.iter().map(DayReport::duration).sum::<Duration>();
+    table.push(format!("| **Total** | {} | | | | | {:?} |", stars, total));
+
+    table.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        history::Record,
+        report::{markdown, DayReport},
+    };
+
+    fn records() -> Vec<Record> {
+        let mut records = vec![
+            Record::new(1, "abc", 1, 0, "default", Duration::from_millis(4)),
+            Record::new(1, "abc", 2, 0, "default", Duration::from_micros(150)),
+            Record::new(1, "abc", 2, 1, "default", Duration::from_micros(180)),
+        ];
+        for (record, answer) in records.iter_mut().zip(["54249", "2727", "56580"]) {
+            record.answer = Some(answer.to_string());
+        }
+        records
+    }
+
+    #[test]
+    fn test1() {
+        let days = [
+            DayReport::new(1, 1, &records()),
+            DayReport::new(2, 2, &records()),
+        ];
+        let table = markdown(&days, false);
+        let lines = table.lines().collect::<Vec<&str>>();
+        assert!(lines.len() == 5);
+        assert!(lines[2] == "| 1 | ★ | `54249` | 4ms | - | - | 4ms |");
+        assert!(lines[3] == "| 2 | ★★ | `2727` | 150µs | `56580` | 180µs | 330µs |");
+        assert!(lines[4] == "| **Total** | 3 | | | | | 4.33ms |");
+    }
+
+    #[test]
+    fn test2() {
+        let days = [DayReport::new(2, 2, &records())];
+        let table = markdown(&days, true);
+        assert!(!table.contains("2727") && !table.contains("56580"));
+        assert!(
+            table.lines().nth(2).unwrap()
+                == "| 2 | ★★ | *redacted* | 150µs | *redacted* | 180µs | 330µs |"
+        );
+    }
+}
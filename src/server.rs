@@ -0,0 +1,263 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::{
+    arith::Answer,
+    error::ParseError,
+    registry::{self, Implementation},
+};
+
+/// How long a solver may run before the request is answered with an error.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Answer a request, returning its status code and JSON body.
+///
+/// `POST /solve/{day}/{part}[?impl=<name>]` solves the part on the input sent as
+/// the request body, once the input has been validated against what the part requires.
+pub fn handle(method: &Method, url: &str, body: &str) -> (u16, Value) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments = path.trim_matches('/').split('/').collect::<Vec<&str>>();
+    let ["solve", day, part] = segments[..] else {
+        return (
+            404,
+            json!({ "error": format!("no such endpoint `{}`", path) }),
+        );
+    };
+    if *method != Method::Post {
+        return (405, json!({ "error": "expected a POST request" }));
+    }
+
+    let (Ok(day), Ok(part)) = (day.parse::<u32>(), part.parse::<usize>()) else {
+        return (400, json!({ "error": "day and part must be numbers" }));
+    };
+    let Some(solution) = registry::get(day) else {
+        return (
+            404,
+            json!({ "error": format!("day {} is not covered", day) }),
+        );
+    };
+    if part == 0 || part > solution.parts.len() {
+        return (
+            404,
+            json!({ "error": format!("day {} has no part {}", day, part) }),
+        );
+    }
+
+    let name = query
+        .split('&')
+        .find_map(|param| param.strip_prefix("impl="));
+    if let Some(name) =
+        name.filter(|name| !solution.parts[part - 1].iter().any(|i| i.name == *name))
+    {
+        return (
+            404,
+            json!({ "error": format!("no implementation named `{}`", name) }),
+        );
+    }
+    let implementation = solution.implementation(part - 1, name);
+
+    let input = body.lines().map(String::from).collect::<Vec<String>>();
+    match solve(solution.validate, part - 1, implementation, input, TIMEOUT) {
+        Ok((answer, nanos)) => (
+            200,
+            json!({
+                "day": day,
+                "part": part,
+                "implementation": implementation.name,
+                "answer": answer.to_string(),
+                "nanos": nanos,
+            }),
+        ),
+        Err(response) => response,
+    }
+}
+
+/// Validate the input for the part of index `part` then solve it on a worker
+/// thread, so that a panicking or endless day does not take the server down.
+///
+/// Returns the answer and its duration in nanoseconds, or the status and body of
+/// the failure. A solver that times out is left running in the background.
+fn solve(
+    validate: fn(&[String], usize) -> Vec<ParseError>,
+    part: usize,
+    implementation: &Implementation,
+    input: Vec<String>,
+    timeout: Duration,
+) -> std::result::Result<(Answer, u64), (u16, Value)> {
+    let (sender, receiver) = mpsc::channel();
+    let solve = implementation.solve;
+    thread::spawn(move || {
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            let violations = validate(&input, part);
+            if !violations.is_empty() {
+                return Err(violations
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<String>>());
+            }
+            let start = Instant::now();
+            let res = solve(&input);
+            Ok((res, start.elapsed().as_nanos() as u64))
+        }));
+        let _ = sender.send(res);
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(Ok(Ok((Ok(answer), nanos)))) => Ok((answer, nanos)),
+        Ok(Ok(Ok((Err(e), _)))) => Err((422, json!({ "error": e.to_string() }))),
+        Ok(Ok(Err(violations))) => {
+            let error = format!("{} violation(s) found", violations.len());
+            Err((422, json!({ "error": error, "violations": violations })))
+        }
+        Ok(Err(payload)) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown cause".to_string());
+            Err((
+                500,
+                json!({ "error": format!("the solver panicked: {}", message) }),
+            ))
+        }
+        Err(mpsc::RecvTimeoutError::Timeout) => Err((
+            504,
+            json!({ "error": format!("no answer after {:?}", timeout) }),
+        )),
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            Err((500, json!({ "error": "the solver stopped unexpectedly" })))
+        }
+    }
+}
+
+/// Serve requests one at a time until the process is stopped.
+pub fn serve(addr: &str) -> Result<()> {
+    let server = Server::http(addr).map_err(|e| anyhow!("cannot listen on {}: {}", addr, e))?;
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    eprintln!("listening on http://{}", addr);
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, value) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => handle(request.method(), request.url(), &body),
+            Err(_) => (400, json!({ "error": "the body must be UTF-8 text" })),
+        };
+        let response = Response::from_string(value.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(e) = request.respond(response) {
+            eprintln!("cannot respond: {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tiny_http::Method;
+
+    use crate::{
+        arith::Answer,
+        registry::Implementation,
+        server::{handle, solve},
+    };
+
+    const INPUT: &str = "\
+Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
+";
+
+    #[test]
+    fn test1() {
+        let (status, body) = handle(&Method::Post, "/solve/2/1", INPUT);
+        assert!(status == 200);
+        assert!(body["answer"] == "8" && body["implementation"] == "default");
+
+        let (status, body) = handle(&Method::Post, "/solve/2/2?impl=default", INPUT);
+        assert!(status == 200 && body["answer"] == "2286");
+    }
+
+    #[test]
+    fn test2() {
+        assert!(handle(&Method::Get, "/solve/2/1", INPUT).0 == 405);
+        assert!(handle(&Method::Post, "/solve/2/3", INPUT).0 == 404);
+        assert!(handle(&Method::Post, "/solve/42/1", INPUT).0 == 404);
+        assert!(handle(&Method::Post, "/solve/2/1?impl=fast", INPUT).0 == 404);
+        assert!(handle(&Method::Post, "/answers", INPUT).0 == 404);
+
+        let (status, body) = handle(&Method::Post, "/solve/2/1", "Round 1: 3 blue");
+        assert!(status == 422);
+        assert!(body["violations"][0]
+            .as_str()
+            .unwrap()
+            .contains("expected `Game <id>:`"));
+    }
+
+    #[test]
+    fn test3() {
+        let (status, body) = handle(&Method::Post, "/solve/8/1", "LR\n\nAAA = (BBB, BBB)");
+        assert!(status == 422 && body["violations"].as_array().unwrap().len() == 3);
+
+        let input = vec!["1".to_string()];
+        let valid = |_: &[String], _| Vec::new();
+        let panicking = Implementation::new("panic", |_| panic!("boom"));
+        let (status, body) =
+            solve(valid, 0, &panicking, input.clone(), Duration::from_secs(10)).unwrap_err();
+        assert!(status == 500 && body["error"] == "the solver panicked: boom");
+
+        // validation runs on the worker too
+        let default = Implementation::new("default", |_| Ok(Answer::zero()));
+        let (status, _) = solve(
+            |_, _| panic!("boom"),
+            0,
+            &default,
+            input.clone(),
+            Duration::from_secs(10),
+        )
+        .unwrap_err();
+        assert!(status == 500);
+
+        let endless = Implementation::new("endless", |_| loop {
+            std::thread::sleep(Duration::from_secs(1));
+        });
+        assert!(
+            solve(valid, 0, &endless, input, Duration::from_millis(10))
+                .unwrap_err()
+                .0
+                == 504
+        );
+    }
+
+    #[test]
+    fn test4() {
+        // part 2 of day 8 starts from the `..A` nodes, without `AAA` nor `ZZZ`
+        let input =
+            "LR\n\n11A = (11B, XXX)\n11B = (XXX, 11Z)\n11Z = (11B, XXX)\n22A = (22B, XXX)\n\
+                     22B = (22C, 22C)\n22C = (22Z, 22Z)\n22Z = (22B, 22B)\nXXX = (XXX, XXX)";
+        let (status, body) = handle(&Method::Post, "/solve/8/2", input);
+        assert!(status == 200 && body["answer"] == "6");
+        let (status, body) = handle(&Method::Post, "/solve/8/1", input);
+        assert!(status == 422 && body["violations"].as_array().unwrap().len() == 2);
+
+        // a color missing from the bag makes the game impossible, not invalid
+        let (status, body) = handle(
+            &Method::Post,
+            "/solve/2/1",
+            "Game 1: 3 blue\nGame 2: 1 cyan",
+        );
+        assert!(status == 200 && body["answer"] == "1");
+    }
+}
@@ -0,0 +1,49 @@
+use std::sync::OnceLock;
+
+/// Value of a day replacing its built-in default, set once from the command line
+/// before solving.
+pub struct Setting<T> {
+    name: &'static str,
+    value: OnceLock<T>,
+    default: fn() -> T,
+}
+
+impl<T> Setting<T> {
+    pub const fn new(name: &'static str, default: fn() -> T) -> Self {
+        Setting {
+            name,
+            value: OnceLock::new(),
+            default,
+        }
+    }
+
+    /// Use `value` instead of the default, before solving.
+    pub fn set(&self, value: T) {
+        if self.value.set(value).is_err() {
+            panic!("{} already in use", self.name);
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        self.value.get_or_init(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use crate::setting::Setting;
+
+    #[test]
+    fn test1() {
+        let setting = Setting::new("answer", || 42);
+        assert!(*setting.get() == 42);
+
+        let setting = Setting::new("answer", || 42);
+        setting.set(7);
+        assert!(*setting.get() == 7);
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| setting.set(8))).is_err());
+        assert!(*setting.get() == 7);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{error::ParseError, output::Output};
+
+/// Check that every line has the same length as the first one.
+pub fn rectangular(day: u32, input: &[String]) -> Vec<ParseError> {
+    let Some(first) = input.first() else {
+        return vec![ParseError::line(day, 0, "", "empty grid")];
+    };
+    let ncol = first.chars().count();
+
+    input
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.chars().count() != ncol)
+        .map(|(i, line)| {
+            // point at the extra characters, or right after the end of a short line
+            let start = line.char_indices().nth(ncol).map_or(line.len(), |(x, _)| x);
+            ParseError::new(
+                day,
+                i,
+                line,
+                start..line.len(),
+                format!(
+                    "line is {} characters long, expected {}",
+                    line.chars().count(),
+                    ncol
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Report every character of the grid rejected by `allowed`.
+pub fn tiles(day: u32, input: &[String], allowed: impl Fn(char) -> bool) -> Vec<ParseError> {
+    input
+        .iter()
+        .enumerate()
+        .flat_map(|(i, line)| {
+            line.char_indices()
+                .filter(|(_, c)| !allowed(*c))
+                .map(move |(x, c)| {
+                    ParseError::new(day, i, line, x..x + c.len_utf8(), "unknown tile")
+                })
+        })
+        .collect()
+}
+
+/// Print all violations, returning whether the input is valid.
+pub fn report(out: &mut Output, violations: &[ParseError]) -> bool {
+    for v in violations {
+        out.eprintln(format!("{}\n", v));
+    }
+    if !violations.is_empty() {
+        out.eprintln(format!("{} violation(s) found", violations.len()));
+    }
+    violations.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validate::{rectangular, tiles};
+
+    #[test]
+    fn test1() {
+        let input = vec!["...".to_string(), "....".into(), "..".into()];
+        let violations = rectangular(10, &input);
+        assert!(violations.len() == 2);
+        assert!(violations[0].line == 2 && violations[0].column == 4 && violations[0].text == ".");
+        assert!(
+            violations[1].line == 3 && violations[1].column == 3 && violations[1].text.is_empty()
+        );
+    }
+
+    #[test]
+    fn test2() {
+        let input = vec![".#.".to_string(), "#x.".into()];
+        let violations = tiles(11, &input, |c| c == '.' || c == '#');
+        assert!(violations.len() == 1 && violations[0].line == 2 && violations[0].column == 2);
+    }
+}